@@ -6,6 +6,7 @@ use abi_stable::{
     std_types::{ROption, RString, RVec},
     StableAbi,
 };
+use serde::Deserialize;
 
 #[repr(C)]
 #[derive(StableAbi)]
@@ -14,9 +15,18 @@ use abi_stable::{
 pub struct Plugin {
     pub init: extern "C" fn(RString),
     pub info: extern "C" fn() -> PluginInfo,
-    pub get_matches: extern "C" fn(RString) -> u64,
+    /// Takes the input and the sort mode the user picked for the plugin
+    pub get_matches: extern "C" fn(RString, SortMode) -> u64,
     pub poll_matches: extern "C" fn(u64) -> PollResult,
     pub handle_selection: extern "C" fn(Match) -> HandleResult,
+    /// The status message of the plugin, shown under its matches. Used to surface problems like
+    /// failed network requests.
+    pub status: extern "C" fn() -> ROption<RString>,
+    /// Whether the plugin asked to be queried again with the current input since the last call,
+    /// for example because data it loads in the background has become available.
+    pub refresh_requested: extern "C" fn() -> bool,
+    /// How far `init`, which runs in a thread of the plugin, has gotten
+    pub init_state: extern "C" fn() -> InitState,
 }
 
 /// Info of the plugin. Used for the main UI
@@ -26,6 +36,49 @@ pub struct PluginInfo {
     pub name: RString,
     /// The icon name from the icon theme in use
     pub icon: RString,
+    /// Binaries the plugin needs to find in `$PATH` to function, anyrun will show
+    /// a warning under the plugin if any of them are missing.
+    pub requires: RVec<RString>,
+    /// Keybinds of the plugin, which are active while one of its matches is selected. The selected
+    /// match is passed to the handler with `keybind` set to the ID of the keybind.
+    pub keybinds: RVec<PluginKeybind>,
+    /// The prefix the input has to start with for the plugin to return matches, if it has one.
+    /// Used to indicate which plugin will respond to the input. Outside of its exclusive mode, the
+    /// plugin is only queried if the input starts with the prefix, and is passed the input after it.
+    pub prefix: ROption<RString>,
+    /// Pass the whole input including the prefix to the plugin, for plugins that need to see it
+    pub raw_input: bool,
+    /// The orders the plugin can sort its matches in, the first one is the default. The user can
+    /// pick one of them for the plugin, which is passed to `get_matches`. Empty if the plugin only
+    /// sorts by relevance.
+    pub sort_modes: RVec<SortMode>,
+}
+
+/// An order for the matches of a plugin
+#[repr(C)]
+#[derive(StableAbi, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// The best matches for the input first
+    #[default]
+    Relevance,
+    /// Alphabetically by the title
+    Name,
+    /// The most recently selected first
+    Recency,
+    /// The most often selected first
+    Frequency,
+}
+
+/// A keybind of a plugin
+#[repr(C)]
+#[derive(StableAbi, Debug, Clone)]
+pub struct PluginKeybind {
+    /// The name of the key, as in `gdk/gdkkeysyms.h` without the `GDK_KEY_` prefix
+    pub key: RString,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// For the plugin to differentiate between its keybinds
+    pub id: u64,
 }
 
 /// Represents a match from a plugin
@@ -43,6 +96,31 @@ pub struct Match {
     pub icon: ROption<RString>,
     /// For runners to differentiate between the matches. Not required.
     pub id: ROption<u64>,
+    /// The score the plugin ranked the match with, used for sorting the matches of all plugins with
+    /// `global_sort` and for debugging the ranking with `--debug-scores`. Not required.
+    pub score: ROption<i64>,
+    /// A small label shown on the right side of the match, for counts or status indicators
+    pub badge: ROption<RString>,
+    /// A hint shown dimmed on the right side of the match, for example the available keyboard
+    /// shortcuts
+    pub hint: ROption<RString>,
+    /// The text the entry is completed to when Tab is pressed while the match is selected
+    pub completion: ROption<RString>,
+    /// Whether the match should be selected instead of the first one when the matches are shown,
+    /// for example to select "Cancel" in a confirmation menu. Not required.
+    pub default_selected: ROption<bool>,
+    /// Whether the match should be run in a terminal. Set to `RSome(false)` by plugins for matches
+    /// that can also be run in one, anyrun sets it to `RSome(true)` when the match is activated with
+    /// Ctrl+Enter. Plugins can use `spawn_in_terminal` to run it.
+    pub term: ROption<bool>,
+    /// The ID of the plugin keybind the match was activated with, set by anyrun
+    pub keybind: ROption<u64>,
+    /// The path of an image file shown instead of the icon, at twice the size of the icon. For
+    /// previews like thumbnails. If it can't be loaded, the icon is shown instead.
+    pub image: ROption<RString>,
+    /// The indices of the characters in the title that matched the input, shown in bold when
+    /// `highlight_matches` is enabled. Ignored if `use_pango` is set. Not required.
+    pub highlight: ROption<RVec<u64>>,
 }
 
 /// For determining how anyrun should proceed after the plugin has handled a match selection
@@ -58,6 +136,19 @@ pub enum HandleResult {
     Copy(RVec<u8>),
     /// Output the content to stdout, printing to stdout has issues in plugins.
     Stdout(RVec<u8>),
+    /// Pass the input to another plugin, which will be put into exclusive mode with the input in
+    /// the search box. The target plugin is identified by the `name` in its `PluginInfo`.
+    Pipe { plugin: RString, input: RString },
+}
+
+/// The state of the `init` of a plugin
+#[repr(C)]
+#[derive(StableAbi, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InitState {
+    Running,
+    Done,
+    /// `init` panicked, the plugin has no data to work with
+    Failed,
 }
 
 #[repr(C)]
@@ -1,6 +1,6 @@
 use proc_macro::{Span, TokenStream};
 use quote::quote;
-use syn::{parse_macro_input, parse_quote, Ident, ReturnType, Type};
+use syn::{parse_macro_input, parse_quote, Expr, Ident, ReturnType, Type};
 
 /// The function to handle the selection of an item. Takes a `Match` as its first argument, and the second argument can be one of:
 /// - &T
@@ -38,12 +38,12 @@ pub fn handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     quote! {
+        #function
+
         #[::abi_stable::sabi_extern_fn]
         fn anyrun_internal_handle_selection(
             selection: ::anyrun_plugin::anyrun_interface::Match,
         ) -> ::anyrun_plugin::anyrun_interface::HandleResult {
-            #function
-
             #fn_name(
                 selection,
                 #data
@@ -59,7 +59,8 @@ pub fn handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// - <Nothing>
 /// where T is the type returned by `init`.
 ///
-/// It should return an `RVec` of `Match`es.
+/// It should return an `RVec` of `Match`es. Plugins listing `sort_modes` in their `PluginInfo` sort
+/// them in the order returned by `anyrun_plugin::sort_mode`.
 #[proc_macro_attribute]
 pub fn get_matches(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let function = parse_macro_input!(item as syn::ItemFn);
@@ -85,9 +86,12 @@ pub fn get_matches(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
         };
         quote! {
+            // The lock is held in the `else` branch too, so `init` can't finish in between and
+            // miss the flag
             if let ::core::option::Option::Some(data) = #data {
                 #fn_name(input, data)
             } else {
+                ANYRUN_INTERNAL_QUERIED_EARLY.store(true, ::std::sync::atomic::Ordering::Relaxed);
                 ::abi_stable::std_types::RVec::new()
             }
         }
@@ -98,16 +102,20 @@ pub fn get_matches(_attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     quote! {
-        #[::abi_stable::sabi_extern_fn]
-        fn anyrun_internal_get_matches(input: ::abi_stable::std_types::RString) -> u64 {
-            #function
+        #function
 
+        #[::abi_stable::sabi_extern_fn]
+        fn anyrun_internal_get_matches(
+            input: ::abi_stable::std_types::RString,
+            sort_mode: ::anyrun_plugin::SortMode,
+        ) -> u64 {
             let current_id =
                 ANYRUN_INTERNAL_ID_COUNTER.load(::std::sync::atomic::Ordering::Relaxed);
             ANYRUN_INTERNAL_ID_COUNTER
                 .store(current_id + 1, ::std::sync::atomic::Ordering::Relaxed);
 
             let handle = ::std::thread::spawn(move || {
+                ::anyrun_plugin::set_sort_mode(sort_mode);
                 #fn_call
             });
 
@@ -119,18 +127,33 @@ pub fn get_matches(_attr: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
-/// Function that returns the plugin info as a `PluginInfo` object. Takes no arguments.
+/// Function that returns the plugin info as a `PluginInfo` object. Takes either no arguments, or an `Option<&T>`
+/// where T is the type returned by `init`. The data is `None` if it is not available yet, for example when `init`
+/// is still running.
 #[proc_macro_attribute]
 pub fn info(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let function = parse_macro_input!(item as syn::ItemFn);
     let fn_name = &function.sig.ident;
 
+    let fn_call = if function.sig.inputs.len() == 1 {
+        quote! {
+            match ANYRUN_INTERNAL_DATA.try_read() {
+                ::core::result::Result::Ok(data) => #fn_name(data.as_ref()),
+                ::core::result::Result::Err(_) => #fn_name(::core::option::Option::None),
+            }
+        }
+    } else {
+        quote! {
+            #fn_name()
+        }
+    };
+
     quote! {
+        #function
+
         #[::abi_stable::sabi_extern_fn]
         fn anyrun_internal_info() -> ::anyrun_plugin::anyrun_interface::PluginInfo {
-            #function
-
-            #fn_name()
+            #fn_call
         }
     }
     .into()
@@ -160,6 +183,9 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
             ::std::sync::atomic::AtomicU64::new(0);
         static ANYRUN_INTERNAL_DATA: ::std::sync::RwLock<Option<#data_type>> =
             ::std::sync::RwLock::new(None);
+        // Set if matches were requested before the data was available
+        static ANYRUN_INTERNAL_QUERIED_EARLY: ::std::sync::atomic::AtomicBool =
+            ::std::sync::atomic::AtomicBool::new(false);
 
         #[::abi_stable::export_root_module]
         fn anyrun_internal_init_root_module() -> ::anyrun_plugin::anyrun_interface::PluginRef {
@@ -170,6 +196,9 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 get_matches: anyrun_internal_get_matches,
                 poll_matches: anyrun_internal_poll_matches,
                 handle_selection: anyrun_internal_handle_selection,
+                status: anyrun_internal_status,
+                refresh_requested: anyrun_internal_refresh_requested,
+                init_state: anyrun_internal_init_state,
             }
             .leak_into_prefix()
         }
@@ -193,17 +222,187 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             let (thread, _) = ANYRUN_INTERNAL_THREAD.lock().unwrap().take().unwrap();
-            ::anyrun_plugin::anyrun_interface::PollResult::Ready(thread.join().unwrap())
+            match thread.join() {
+                ::core::result::Result::Ok(matches) => {
+                    ::anyrun_plugin::anyrun_interface::PollResult::Ready(matches)
+                }
+                // Report the panic instead of taking down anyrun with it
+                ::core::result::Result::Err(_) => {
+                    ::std::eprintln!("A plugin panicked while getting matches");
+                    ::anyrun_plugin::set_status(::core::option::Option::Some(
+                        "The plugin crashed while getting matches".to_string(),
+                    ));
+                    ::anyrun_plugin::anyrun_interface::PollResult::Ready(
+                        ::abi_stable::std_types::RVec::new(),
+                    )
+                }
+            }
         }
 
         #[::abi_stable::sabi_extern_fn]
-        fn anyrun_internal_init(config_dir: ::abi_stable::std_types::RString) {
-            #function
+        fn anyrun_internal_status() -> ::abi_stable::std_types::ROption<::abi_stable::std_types::RString> {
+            ::anyrun_plugin::current_status()
+                .map(::abi_stable::std_types::RString::from)
+                .into()
+        }
+
+        #[::abi_stable::sabi_extern_fn]
+        fn anyrun_internal_refresh_requested() -> bool {
+            ::anyrun_plugin::take_refresh_request()
+        }
+
+        #[::abi_stable::sabi_extern_fn]
+        fn anyrun_internal_init_state() -> ::anyrun_plugin::anyrun_interface::InitState {
+            // `init` holds the write lock until it is done, so the lock is poisoned if it panicked
+            match ANYRUN_INTERNAL_DATA.try_read() {
+                ::core::result::Result::Ok(data) if data.is_some() => {
+                    ::anyrun_plugin::anyrun_interface::InitState::Done
+                }
+                ::core::result::Result::Err(::std::sync::TryLockError::Poisoned(_)) => {
+                    ::anyrun_plugin::anyrun_interface::InitState::Failed
+                }
+                _ => ::anyrun_plugin::anyrun_interface::InitState::Running,
+            }
+        }
 
+        #function
+
+        #[::abi_stable::sabi_extern_fn]
+        fn anyrun_internal_init(config_dir: ::abi_stable::std_types::RString) {
             ::std::thread::spawn(|| {
                 let mut lock = ANYRUN_INTERNAL_DATA.write().unwrap();
                 *lock = ::core::option::Option::Some(#fn_name(config_dir));
+                ::std::mem::drop(lock);
+                // Queries that came in before the lock was taken got no matches
+                if ANYRUN_INTERNAL_QUERIED_EARLY.swap(false, ::std::sync::atomic::Ordering::Relaxed) {
+                    ::anyrun_plugin::request_refresh();
+                }
+            });
+        }
+    }
+    .into()
+}
+
+/// Derive macro for plugin configs, generating the `Default` implementation and the
+/// `anyrun_plugin::PluginConfig` implementation from `#[config(...)]` attributes on the fields, which
+/// allows loading the config with `anyrun_plugin::load_config`. The following options are supported:
+/// - `default = <expr>`: The default value of the field, `Default::default()` is used if it is not specified
+/// - `min = <expr>`: The minimum allowed value of the field
+/// - `max = <expr>`: The maximum allowed value of the field
+///
+/// `validate` returns an error describing the first field that is out of range, and `reset_invalid`
+/// resets the fields that are out of range to their defaults.
+#[proc_macro_derive(PluginConfig, attributes(config))]
+pub fn plugin_config(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as syn::ItemStruct);
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let mut defaults = quote!();
+    let mut checks = quote!();
+    let mut resets = quote!();
+    let mut setters = quote!();
+
+    for field in item.fields.iter() {
+        let field_ident = match &field.ident {
+            Some(field_ident) => field_ident,
+            None => {
+                return quote! { compile_error!("PluginConfig can only be derived for structs with named fields."); }
+                    .into()
+            }
+        };
+        let mut default = quote!(::core::default::Default::default());
+        // The comparisons a valid value fails, with the bound and the error
+        let mut bounds = Vec::new();
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("config"))
+        {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    let expr: Expr = meta.value()?.parse()?;
+                    default = quote!(#expr);
+                } else if meta.path.is_ident("min") {
+                    let expr: Expr = meta.value()?.parse()?;
+                    let error = format!("`{}` must be at least {}", field_ident, quote!(#expr));
+                    bounds.push((quote!(<), expr, error));
+                } else if meta.path.is_ident("max") {
+                    let expr: Expr = meta.value()?.parse()?;
+                    let error = format!("`{}` must be at most {}", field_ident, quote!(#expr));
+                    bounds.push((quote!(>), expr, error));
+                } else {
+                    return Err(meta.error("Expected `default`, `min` or `max`."));
+                }
+                Ok(())
             });
+
+            if let Err(why) = result {
+                return why.to_compile_error().into();
+            }
+        }
+
+        for (comparison, bound, error) in bounds {
+            checks = quote! {
+                #checks
+                if self.#field_ident #comparison #bound {
+                    return ::core::result::Result::Err(#error.to_string());
+                }
+            };
+            resets = quote! {
+                #resets
+                if self.#field_ident #comparison #bound {
+                    errors.push(#error.to_string());
+                    self.#field_ident = #default;
+                }
+            };
+        }
+
+        defaults = quote! {
+            #defaults
+            #field_ident: #default,
+        };
+
+        let name = field_ident.to_string();
+        setters = quote! {
+            #setters
+            #name => self.#field_ident = value.into_rust().map_err(|why| why.to_string())?,
+        };
+    }
+
+    quote! {
+        impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #defaults
+                }
+            }
+        }
+
+        impl #impl_generics ::anyrun_plugin::PluginConfig for #ident #ty_generics #where_clause {
+            fn set_field(
+                &mut self,
+                name: &str,
+                value: ::anyrun_plugin::config::Value,
+            ) -> ::core::result::Result<(), ::std::string::String> {
+                match name {
+                    #setters
+                    _ => return ::core::result::Result::Err(::std::format!("unknown field `{}`", name)),
+                }
+                ::core::result::Result::Ok(())
+            }
+
+            fn validate(&self) -> ::core::result::Result<(), ::std::string::String> {
+                #checks
+                ::core::result::Result::Ok(())
+            }
+
+            fn reset_invalid(&mut self) -> ::std::vec::Vec<::std::string::String> {
+                let mut errors = ::std::vec::Vec::new();
+                #resets
+                errors
+            }
         }
     }
     .into()
@@ -211,7 +410,7 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 pub fn config_args(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let item = parse_macro_input!(item as syn::ItemStruct);
+    let mut item = parse_macro_input!(item as syn::ItemStruct);
     let ident = &item.ident;
 
     let mut opt_item = item.clone();
@@ -219,6 +418,28 @@ pub fn config_args(_attr: TokenStream, item: TokenStream) -> TokenStream {
     opt_item.attrs = vec![parse_quote!(#[derive(::clap::Args)])];
     opt_item.ident = Ident::new(&format!("{}Args", opt_item.ident), Span::call_site().into());
 
+    // Fields marked with `#[config_args(skip)]` can't be set with arguments, for example if they
+    // can't be parsed by clap
+    let is_skipped = |field: &syn::Field| {
+        field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("config_args"))
+    };
+    if let syn::Fields::Named(fields) = &mut opt_item.fields {
+        fields.named = fields
+            .named
+            .clone()
+            .into_iter()
+            .filter(|field| !is_skipped(field))
+            .collect();
+    }
+    for field in item.fields.iter_mut() {
+        field
+            .attrs
+            .retain(|attr| !attr.path().is_ident("config_args"));
+    }
+
     let opt_ident = &opt_item.ident;
 
     let mut operations = quote!();
@@ -0,0 +1,100 @@
+/*!
+Helpers for testing Anyrun plugins.
+
+The functions marked with `init`, `info`, `get_matches` and `handler` stay callable as normal
+functions, so tests can call them directly instead of going through anyrun. Add this crate as a
+dev-dependency of the plugin and test it in a `#[cfg(test)]` module of the plugin:
+
+```ignore
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyrun_plugin_testing::*;
+
+    #[test]
+    fn matches_the_input() {
+        let config_dir = ConfigDir::new().with_file("demo.ron", "Config(prefix: \":demo\")");
+        let state = init(config_dir.path());
+
+        let matches = get_matches("test".into(), &state);
+        assert_eq!(titles(&matches), ["Test match"]);
+
+        let selection = find_match(&matches, "Test match").unwrap().clone();
+        assert!(matches!(handler(selection, &state), HandleResult::Close));
+    }
+}
+```
+!*/
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use abi_stable::std_types::RString;
+use anyrun_plugin::Match;
+
+static DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary config directory for passing to `init`, which is removed when it is dropped. Tests
+/// run in parallel, so every directory is unique.
+pub struct ConfigDir {
+    path: PathBuf,
+}
+
+impl ConfigDir {
+    /// Create an empty config directory
+    pub fn new() -> Self {
+        let path = env::temp_dir().join(format!(
+            "anyrun-plugin-test-{}-{}",
+            process::id(),
+            DIR_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).expect("Failed to create the config directory");
+        Self { path }
+    }
+
+    /// Write a file to the config directory, like the config file of the plugin
+    pub fn with_file(self, name: &str, content: &str) -> Self {
+        fs::write(self.path.join(name), content).expect("Failed to write the config file");
+        self
+    }
+
+    /// The path of the directory, as `init` takes it
+    pub fn path(&self) -> RString {
+        self.path.to_string_lossy().into_owned().into()
+    }
+}
+
+impl Default for ConfigDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ConfigDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The titles of the matches in order
+pub fn titles(matches: &[Match]) -> Vec<String> {
+    matches
+        .iter()
+        .map(|_match| _match.title.to_string())
+        .collect()
+}
+
+/// The first match with the title, for passing to the handler
+pub fn find_match<'a>(matches: &'a [Match], title: &str) -> Option<&'a Match> {
+    matches.iter().find(|_match| _match.title.as_str() == title)
+}
+
+/// The status the plugin set with `set_status`. The status is shared by the tests of the plugin,
+/// so tests checking it should not run in parallel with others that set it.
+pub fn status() -> Option<String> {
+    anyrun_plugin::current_status()
+}
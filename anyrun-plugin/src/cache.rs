@@ -0,0 +1,66 @@
+//! Caching data that is slow to build, like an index of files, across runs of anyrun.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The modification times of the sources, the cache is stale if any of them changed
+type Fingerprint = Vec<(PathBuf, Option<u64>)>;
+
+/// The directory anyrun stores its caches in, `$XDG_CACHE_HOME/anyrun`
+pub(crate) fn cache_dir() -> PathBuf {
+    env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(format!("{}/.cache", env::var("HOME").unwrap_or_default()))
+        })
+        .join("anyrun")
+}
+
+fn cache_path(name: &str) -> PathBuf {
+    cache_dir().join(format!("{}-cache.ron", name))
+}
+
+fn fingerprint(sources: &[PathBuf]) -> Fingerprint {
+    sources
+        .iter()
+        .map(|source| (source.clone(), modified(source)))
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+/// Load the cache called `name`, if it exists and none of the `sources` it was built from have
+/// been modified since it was stored.
+///
+/// The sources are usually directories, whose modification time changes when files are added,
+/// removed or renamed in them, but not when a file in them is edited in place.
+pub fn load_cache<T: DeserializeOwned>(name: &str, sources: &[PathBuf]) -> Option<T> {
+    let content = fs::read_to_string(cache_path(name)).ok()?;
+    let (stored, value) = ron::from_str::<(Fingerprint, T)>(&content).ok()?;
+
+    (stored == fingerprint(sources)).then_some(value)
+}
+
+/// Store `value` as the cache called `name`, built from the `sources`
+pub fn store_cache<T: Serialize>(name: &str, sources: &[PathBuf], value: &T) {
+    let path = cache_path(name);
+    let result = fs::create_dir_all(cache_dir())
+        .map_err(|why| why.to_string())
+        .and_then(|_| ron::to_string(&(fingerprint(sources), value)).map_err(|why| why.to_string()))
+        .and_then(|content| fs::write(&path, content).map_err(|why| why.to_string()));
+
+    if let Err(why) = result {
+        eprintln!("Error storing the cache {}: {}", path.display(), why);
+    }
+}
@@ -0,0 +1,110 @@
+//! Loading plugin configs without losing the valid parts of a malformed config file.
+
+use std::{env, fs};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+pub use ron::Value;
+
+/// A config value for sensitive data like API keys, which can be read from an environment variable
+/// to keep it out of the config file.
+///
+/// In the config it is written as either `Value("...")` to use the value as is, or `Env("NAME")` to
+/// read it from the environment variable `NAME`.
+#[derive(Deserialize, Clone, Debug)]
+pub enum Secret {
+    Value(String),
+    Env(String),
+}
+
+impl Secret {
+    /// Get the value, reading the environment variable if needed. Returns `None` and prints an error
+    /// if the environment variable is not set or not valid unicode.
+    pub fn resolve(&self) -> Option<String> {
+        match self {
+            Secret::Value(value) => Some(value.clone()),
+            Secret::Env(name) => match env::var(name) {
+                Ok(value) => Some(value),
+                Err(why) => {
+                    eprintln!("Error reading the environment variable {}: {}", name, why);
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// Implemented by `#[derive(PluginConfig)]`, allows setting the fields of the config one by one.
+pub trait PluginConfig: Default + DeserializeOwned {
+    /// Set the field called `name` from a RON value, failing if the value has the wrong type or the
+    /// field doesn't exist.
+    fn set_field(&mut self, name: &str, value: Value) -> Result<(), String>;
+
+    /// Check that the values of the config are within their allowed ranges
+    fn validate(&self) -> Result<(), String>;
+
+    /// Reset the fields that are out of their allowed ranges to their default values, returning the
+    /// errors for them
+    fn reset_invalid(&mut self) -> Vec<String>;
+}
+
+/// Load the config of a plugin from `<config_dir>/<file_name>`.
+///
+/// If the file can't be parsed as a whole, the fields are set one by one over the defaults, so an
+/// invalid field only resets that field to its default value. All problems are printed, naming the
+/// offending fields. Fields that fail validation are reset to their default values too. If the file
+/// doesn't exist, the default config is used.
+pub fn load_config<T: PluginConfig>(config_dir: &str, file_name: &str) -> T {
+    let path = format!("{}/{}", config_dir, file_name);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(why) => {
+            eprintln!("Error reading {}, using the default config: {}", path, why);
+            return T::default();
+        }
+    };
+
+    let mut config = ron::from_str::<T>(&content).unwrap_or_else(|why| {
+        eprintln!("Error parsing {}: {}", path, why);
+        merge_fields(&path, &content)
+    });
+
+    for why in config.reset_invalid() {
+        eprintln!("Invalid config in {}, using the default: {}", path, why);
+    }
+
+    config
+}
+
+/// Set the valid fields of the config over the defaults
+fn merge_fields<T: PluginConfig>(path: &str, content: &str) -> T {
+    let mut config = T::default();
+
+    let fields = match ron::from_str::<Value>(content) {
+        Ok(Value::Map(fields)) => fields,
+        Ok(_) => {
+            eprintln!(
+                "The config in {} is not a struct, using the default config",
+                path
+            );
+            return config;
+        }
+        Err(why) => {
+            eprintln!("Error parsing {}, using the default config: {}", path, why);
+            return config;
+        }
+    };
+
+    for (name, value) in fields.iter() {
+        if let Value::String(name) = name {
+            if let Err(why) = config.set_field(name, value.clone()) {
+                eprintln!(
+                    "Ignoring `{}` in {}, using the default: {}",
+                    name, path, why
+                );
+            }
+        }
+    }
+
+    config
+}
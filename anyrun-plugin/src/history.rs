@@ -0,0 +1,112 @@
+//! Remembering which matches were selected for which queries, to rank them higher the next time.
+
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use crate::cache::cache_dir;
+
+/// The selections a plugin's users made for their queries, stored in the cache directory.
+///
+/// Plugins call [`SelectionHistory::set_query`] when getting matches and [`SelectionHistory::record`]
+/// when a match is selected, and use [`SelectionHistory::count`] to boost the score of matches that
+/// were selected before for a similar query.
+pub struct SelectionHistory {
+    path: PathBuf,
+    max_entries: usize,
+    /// `(query, value, count)`, ordered from the least to the most recently selected
+    entries: Mutex<Vec<(String, String, u32)>>,
+    /// The query the current matches were requested for
+    query: Mutex<String>,
+}
+
+impl SelectionHistory {
+    /// Load the history called `name`, which keeps at most `max_entries` entries by dropping the
+    /// least recently selected ones.
+    pub fn load(name: &str, max_entries: usize) -> Self {
+        let path = cache_dir().join(format!("{}-selections.ron", name));
+
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| ron::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            max_entries,
+            entries: Mutex::new(entries),
+            query: Mutex::new(String::new()),
+        }
+    }
+
+    /// Set the query the current matches are for, which [`SelectionHistory::record`] uses
+    pub fn set_query(&self, query: &str) {
+        *self.query.lock().unwrap() = query.to_lowercase();
+    }
+
+    /// How often `value` was selected for queries that the current query is a prefix of, or that are a
+    /// prefix of the current query
+    pub fn count(&self, value: &str) -> u32 {
+        let query = self.query.lock().unwrap();
+        if query.is_empty() {
+            return 0;
+        }
+
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_query, _value, _)| {
+                _value == value
+                    && (_query.starts_with(&*query) || query.starts_with(_query.as_str()))
+            })
+            .map(|(_, _, count)| count)
+            .sum()
+    }
+
+    /// How often `value` was selected for any query
+    pub fn total(&self, value: &str) -> u32 {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, _value, _)| _value == value)
+            .map(|(_, _, count)| count)
+            .sum()
+    }
+
+    /// Record that `value` was selected for the current query and save the history
+    pub fn record(&self, value: &str) {
+        let query = self.query.lock().unwrap().clone();
+        if query.is_empty() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        let count = match entries
+            .iter()
+            .position(|(_query, _value, _)| *_query == query && _value == value)
+        {
+            Some(index) => entries.remove(index).2 + 1,
+            None => 1,
+        };
+        entries.push((query, value.to_string(), count));
+
+        if entries.len() > self.max_entries {
+            let excess = entries.len() - self.max_entries;
+            entries.drain(..excess);
+        }
+
+        if let Some(dir) = self.path.parent() {
+            if let Err(why) = fs::create_dir_all(dir) {
+                eprintln!("Error creating the cache directory: {}", why);
+            }
+        }
+        match ron::to_string(&*entries) {
+            Ok(content) => {
+                if let Err(why) = fs::write(&self.path, content) {
+                    eprintln!("Error saving the selection history: {}", why);
+                }
+            }
+            Err(why) => eprintln!("Error serializing the selection history: {}", why),
+        }
+    }
+}
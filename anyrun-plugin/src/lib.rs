@@ -5,8 +5,76 @@ Each plugin needs 4 functions defined, `init`, `info`, `get_matches` and the `ha
 on what each of these should be is found in their respective attribute macros.
 !*/
 
-pub use anyrun_interface::{self, HandleResult, Match, PluginInfo};
-pub use anyrun_macros::{get_matches, handler, info, init};
+pub use anyrun_interface::{self, HandleResult, Match, PluginInfo, PluginKeybind, SortMode};
+pub use anyrun_macros::{get_matches, handler, info, init, PluginConfig};
+pub use cache::{load_cache, store_cache};
+pub use config::{load_config, PluginConfig, Secret};
+pub use history::SelectionHistory;
+pub use terminal::spawn_in_terminal;
+
+pub mod cache;
+pub mod config;
+pub mod history;
+pub mod terminal;
+
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+static STATUS: Mutex<Option<String>> = Mutex::new(None);
+static REFRESH_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Set on the thread `get_matches` runs on
+    static SORT_MODE: Cell<SortMode> = Cell::new(SortMode::default());
+}
+
+/// Sets the status message of the plugin, which is shown under its matches until it is cleared
+/// with `None`. Useful for letting the user know that something went wrong, for example that a
+/// network request failed and the results may be outdated.
+pub fn set_status(status: Option<String>) {
+    *STATUS.lock().unwrap() = status;
+}
+
+#[doc(hidden)]
+pub fn current_status() -> Option<String> {
+    STATUS.lock().unwrap().clone()
+}
+
+/// Asks anyrun to query the plugin again with the current input, replacing its cached matches.
+/// Useful for plugins that load their data in a background thread, so that input typed before
+/// loading finished still gets results. This is done automatically once `init` returns if the
+/// plugin was queried before that.
+pub fn request_refresh() {
+    REFRESH_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn take_refresh_request() -> bool {
+    REFRESH_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// The sort mode the user picked for the plugin, one of the `sort_modes` in its `PluginInfo`. Only
+/// set while `get_matches` runs, `SortMode::Relevance` otherwise.
+pub fn sort_mode() -> SortMode {
+    SORT_MODE.with(Cell::get)
+}
+
+#[doc(hidden)]
+pub fn set_sort_mode(sort_mode: SortMode) {
+    SORT_MODE.with(|cell| cell.set(sort_mode));
+}
+
+/// Whether a fuzzy match with the score should be shown with the `min_score` option of the plugin.
+/// An empty input matches everything with a score of 0, so the threshold only applies once
+/// something was typed, otherwise lists shown without input would be empty.
+pub fn meets_min_score(input: &str, score: i64, min_score: i64) -> bool {
+    input.is_empty() || score >= min_score
+}
 
 /*
 The macro to create a plugin, handles asynchronous execution of getting the matches and the boilerplate
@@ -0,0 +1,34 @@
+//! Running commands in a terminal emulator.
+
+use std::{
+    env, io,
+    process::{Child, Command},
+};
+
+/// Terminals tried in order if no terminal is configured
+const SENSIBLE_TERMINALS: &[&str] = &["alacritty", "foot", "kitty", "wezterm", "wterm"];
+
+/// Run a command in a terminal emulator, with the command and its arguments passed after `-e`.
+///
+/// The terminal used is `terminal` if it is set, otherwise `$TERMINAL`, which anyrun sets from its
+/// `terminal` option. If neither is set, a list of common terminals is tried until one of them
+/// starts.
+pub fn spawn_in_terminal(terminal: Option<&str>, command: &[&str]) -> io::Result<Child> {
+    let terminal = terminal
+        .map(str::to_string)
+        .or_else(|| env::var("TERMINAL").ok().filter(|term| !term.is_empty()));
+
+    match terminal {
+        Some(terminal) => Command::new(terminal).arg("-e").args(command).spawn(),
+        None => {
+            let mut error = io::Error::new(io::ErrorKind::NotFound, "No terminal found");
+            for terminal in SENSIBLE_TERMINALS {
+                match Command::new(terminal).arg("-e").args(command).spawn() {
+                    Ok(child) => return Ok(child),
+                    Err(why) => error = why,
+                }
+            }
+            Err(error)
+        }
+    }
+}
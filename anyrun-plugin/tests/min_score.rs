@@ -0,0 +1,16 @@
+use anyrun_plugin::meets_min_score;
+
+#[test]
+fn threshold() {
+    assert!(meets_min_score("abc", 50, 50));
+    assert!(meets_min_score("abc", 80, 50));
+    assert!(!meets_min_score("abc", 49, 50));
+    assert!(meets_min_score("abc", 0, 0));
+}
+
+#[test]
+fn empty_input_matches_everything() {
+    // The fuzzy matcher scores every candidate 0 for an empty input
+    assert!(meets_min_score("", 0, 50));
+    assert!(meets_min_score("", 0, i64::MAX));
+}
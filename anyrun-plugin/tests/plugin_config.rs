@@ -0,0 +1,82 @@
+use anyrun_plugin::{config::Value, PluginConfig};
+use serde::Deserialize;
+
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
+struct Config {
+    #[config(default = ":test".to_string())]
+    prefix: String,
+    #[config(default = 5, min = 1, max = 10)]
+    max_entries: usize,
+    min_score: i64,
+}
+
+#[test]
+fn defaults() {
+    let config = Config::default();
+    assert_eq!(config.prefix, ":test");
+    assert_eq!(config.max_entries, 5);
+    // Fields without a `default` use `Default::default()`
+    assert_eq!(config.min_score, 0);
+}
+
+#[test]
+fn validate_bounds() {
+    let mut config = Config::default();
+    assert_eq!(config.validate(), Ok(()));
+
+    config.max_entries = 1;
+    assert_eq!(config.validate(), Ok(()));
+    config.max_entries = 10;
+    assert_eq!(config.validate(), Ok(()));
+
+    config.max_entries = 0;
+    assert_eq!(
+        config.validate(),
+        Err("`max_entries` must be at least 1".to_string())
+    );
+    config.max_entries = 11;
+    assert_eq!(
+        config.validate(),
+        Err("`max_entries` must be at most 10".to_string())
+    );
+}
+
+#[test]
+fn set_field() {
+    let mut config = Config::default();
+    assert_eq!(
+        config.set_field("prefix", Value::String(":t".to_string())),
+        Ok(())
+    );
+    assert_eq!(config.prefix, ":t");
+
+    assert!(config
+        .set_field("max_entries", Value::String("many".to_string()))
+        .is_err());
+    assert_eq!(config.max_entries, 5);
+
+    assert_eq!(
+        config.set_field("unknown", Value::Unit),
+        Err("unknown field `unknown`".to_string())
+    );
+}
+
+#[test]
+fn reset_invalid() {
+    let mut config = Config {
+        prefix: ":t".to_string(),
+        max_entries: 0,
+        min_score: 10,
+    };
+    assert_eq!(
+        config.reset_invalid(),
+        ["`max_entries` must be at least 1".to_string()]
+    );
+    // Only the field out of range is reset
+    assert_eq!(config.max_entries, 5);
+    assert_eq!(config.prefix, ":t");
+    assert_eq!(config.min_score, 10);
+
+    assert!(config.reset_invalid().is_empty());
+}
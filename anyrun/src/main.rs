@@ -1,19 +1,26 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    cmp::{Ordering, Reverse},
+    collections::{HashMap, VecDeque},
     env, fs,
-    io::{self, Write},
+    io::{self, Read, Write},
     mem,
-    path::PathBuf,
+    os::unix::io::FromRawFd,
+    path::{Path, PathBuf},
     rc::Rc,
     sync::Once,
-    time::Duration,
+    thread,
+    time::{Duration, Instant},
 };
 
-use abi_stable::std_types::{ROption, RVec};
-use anyrun_interface::{HandleResult, Match, PluginInfo, PluginRef, PollResult};
-use clap::{Parser, ValueEnum};
-use gtk::{gdk, gdk_pixbuf, gio, glib, prelude::*};
+use abi_stable::std_types::{ROption, RString, RVec};
+use anyrun_interface::{
+    HandleResult, InitState, Match, PluginInfo, PluginKeybind, PluginRef, PollResult, SortMode,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use gtk::{gdk, gdk_pixbuf, gio, glib, pango, prelude::*};
 use nix::unistd;
+use regex::Regex;
 use serde::Deserialize;
 use wl_clipboard_rs::copy;
 
@@ -27,7 +34,7 @@ struct Config {
     y: RelativeNum,
 
     #[serde(default = "Config::default_width")]
-    width: RelativeNum,
+    width: Width,
 
     #[serde(default = "Config::default_height")]
     height: RelativeNum,
@@ -49,6 +56,84 @@ struct Config {
     max_entries: Option<usize>,
     #[serde(default = "Config::default_layer")]
     layer: Layer,
+    #[serde(default)]
+    inline_first_result: bool,
+    #[serde(default)]
+    keep_selection: bool,
+    #[serde(default)]
+    exclusive_filter: bool,
+    #[serde(default)]
+    match_cache_size: usize,
+    #[serde(default)]
+    display_order: Vec<PathBuf>,
+    #[serde(default)]
+    #[config_args(skip)]
+    plugin_settings: HashMap<PathBuf, PluginSettings>,
+    #[serde(default)]
+    #[config_args(skip)]
+    aliases: HashMap<String, String>,
+    #[serde(default)]
+    show_result_count: bool,
+    #[serde(default = "Config::default_click")]
+    primary_click: MouseAction,
+    #[serde(default = "Config::default_middle_click")]
+    middle_click: MouseAction,
+    #[serde(default = "Config::default_click")]
+    secondary_click: MouseAction,
+    #[serde(default = "Config::default_icon_size")]
+    icon_size: i32,
+    #[serde(default)]
+    #[config_args(skip)]
+    keybinds: Vec<Keybind>,
+    #[serde(default)]
+    trim_copy: bool,
+    #[serde(default)]
+    max_input_length: Option<usize>,
+    #[serde(default)]
+    input_filter: Option<String>,
+    #[serde(default)]
+    plugin_separator: bool,
+    #[serde(default)]
+    collapse_plugins: bool,
+    #[serde(default)]
+    terminal: Option<String>,
+    #[serde(default)]
+    background_dim: Option<f64>,
+    #[serde(default)]
+    #[config_args(skip)]
+    fallback: Option<Fallback>,
+    #[serde(default)]
+    idle_timeout_secs: Option<u64>,
+    #[serde(default)]
+    combine_mode: CombineMode,
+    #[serde(default)]
+    description_on_select: bool,
+    #[serde(default)]
+    window_name: Option<String>,
+    #[serde(default)]
+    paginate: bool,
+    #[serde(default)]
+    reselect_on_refresh: bool,
+    #[serde(default = "Config::default_query_debounce_ms")]
+    query_debounce_ms: u64,
+    #[serde(default)]
+    highlight_matches: bool,
+    #[serde(default)]
+    placeholder: Option<String>,
+    #[serde(default)]
+    query_on_escape: bool,
+    #[serde(default)]
+    activate_on: ActivateOn,
+    #[serde(default)]
+    quick_select: Option<Modifier>,
+    #[serde(default)]
+    record_copy_history: bool,
+    #[serde(default)]
+    copy_to_primary: bool,
+    #[serde(default)]
+    text_direction: TextDirection,
+    #[serde(default)]
+    global_sort: bool,
 }
 
 impl Config {
@@ -60,8 +145,8 @@ impl Config {
         RelativeNum::Absolute(0)
     }
 
-    fn default_width() -> RelativeNum {
-        RelativeNum::Fraction(0.5)
+    fn default_width() -> Width {
+        Width::Fraction(0.5)
     }
 
     fn default_height() -> RelativeNum {
@@ -80,6 +165,22 @@ impl Config {
     fn default_layer() -> Layer {
         Layer::Overlay
     }
+
+    fn default_icon_size() -> i32 {
+        32
+    }
+
+    fn default_click() -> MouseAction {
+        MouseAction::Nop
+    }
+
+    fn default_middle_click() -> MouseAction {
+        MouseAction::Copy
+    }
+
+    fn default_query_debounce_ms() -> u64 {
+        50
+    }
 }
 
 impl Default for Config {
@@ -97,10 +198,72 @@ impl Default for Config {
             show_results_immediately: false,
             max_entries: None,
             layer: Self::default_layer(),
+            inline_first_result: false,
+            keep_selection: false,
+            exclusive_filter: false,
+            match_cache_size: 0,
+            display_order: Vec::new(),
+            plugin_settings: HashMap::new(),
+            aliases: HashMap::new(),
+            show_result_count: false,
+            primary_click: Self::default_click(),
+            middle_click: Self::default_middle_click(),
+            secondary_click: Self::default_click(),
+            icon_size: Self::default_icon_size(),
+            keybinds: Vec::new(),
+            trim_copy: false,
+            max_input_length: None,
+            input_filter: None,
+            plugin_separator: false,
+            collapse_plugins: false,
+            terminal: None,
+            background_dim: None,
+            fallback: None,
+            idle_timeout_secs: None,
+            combine_mode: CombineMode::Block,
+            description_on_select: false,
+            window_name: None,
+            paginate: false,
+            reselect_on_refresh: false,
+            query_debounce_ms: Self::default_query_debounce_ms(),
+            highlight_matches: false,
+            placeholder: None,
+            query_on_escape: false,
+            activate_on: ActivateOn::SingleClick,
+            quick_select: None,
+            record_copy_history: false,
+            copy_to_primary: false,
+            text_direction: TextDirection::Auto,
+            global_sort: false,
         }
     }
 }
 
+/// Settings for a specific plugin, keyed by the plugin's path in `plugins`
+#[derive(Deserialize, Clone, Default)]
+struct PluginSettings {
+    /// The amount of matches shown before the plugin's list becomes scrollable
+    #[serde(default)]
+    max_visible: Option<usize>,
+    /// Overrides the global `icon_size` for the plugin's matches
+    #[serde(default)]
+    icon_size: Option<i32>,
+    /// The icon theme used for the plugin's matches instead of the system one
+    #[serde(default)]
+    icon_theme: Option<String>,
+    /// The most matches of the plugin that are shown, the rest are dropped in the order the plugin
+    /// returned them
+    #[serde(default)]
+    max_entries: Option<usize>,
+    /// The format of the text copied from the plugin, `{content}` is replaced with what the plugin
+    /// copies and `{title}` and `{description}` with those of the selected match
+    #[serde(default)]
+    copy_format: Option<String>,
+    /// The order of the plugin's matches, one of the sort modes the plugin supports
+    #[serde(default)]
+    sort_mode: Option<SortMode>,
+}
+
 #[derive(Deserialize, Clone, ValueEnum)]
 enum Layer {
     Background,
@@ -109,6 +272,99 @@ enum Layer {
     Overlay,
 }
 
+/// What to do when a match is clicked
+#[derive(Deserialize, Clone, ValueEnum)]
+enum MouseAction {
+    /// Select the match as if Return was pressed
+    Select,
+    /// Copy the title of the match and close
+    Copy,
+    /// Close without selecting anything
+    Close,
+    /// Do nothing, a primary click still moves the selection to the match
+    Nop,
+}
+
+/// Which clicks on a match run the configured click actions
+#[derive(Deserialize, Clone, Copy, ValueEnum, Default)]
+enum ActivateOn {
+    #[default]
+    SingleClick,
+    /// A single click only moves the selection to the match
+    DoubleClick,
+}
+
+/// The direction of the text in the entry and matches, which also mirrors their alignment
+#[derive(Deserialize, Clone, Copy, ValueEnum, Default)]
+enum TextDirection {
+    /// Follow the direction of the locale
+    #[default]
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+/// A modifier key held for keybinds
+#[derive(Deserialize, Clone, Copy, ValueEnum)]
+enum Modifier {
+    Alt,
+    Ctrl,
+}
+
+impl Modifier {
+    fn mask(self) -> gdk::ModifierType {
+        match self {
+            Modifier::Alt => gdk::ModifierType::MOD1_MASK,
+            Modifier::Ctrl => gdk::ModifierType::CONTROL_MASK,
+        }
+    }
+}
+
+/// How the matches of the plugins are put together in the list of matches
+#[derive(Deserialize, Clone, Copy, ValueEnum, Default, PartialEq)]
+enum CombineMode {
+    /// All matches of the first plugin, then all matches of the second one and so on, grouped by
+    /// plugin
+    #[default]
+    Block,
+    /// One match of each plugin in turn until they run out, in a single list
+    RoundRobin,
+}
+
+/// A match shown when no plugin has matches for the input
+#[derive(Deserialize, Clone)]
+struct Fallback {
+    /// The title of the match, `{}` is replaced with the input
+    title: String,
+    /// The command run with `sh -c` when the match is selected, the input is passed to it as `$1`
+    command: String,
+}
+
+/// A key combination that triggers an `Action`
+#[derive(Deserialize, Clone)]
+struct Keybind {
+    /// The name of the key, as in `gdk/gdkkeysyms.h` without the `GDK_KEY_` prefix
+    key: String,
+    #[serde(default)]
+    ctrl: bool,
+    #[serde(default)]
+    alt: bool,
+    action: Action,
+}
+
+#[derive(Deserialize, Clone)]
+enum Action {
+    /// Copy the titles of all shown matches separated by newlines and close
+    CopyAll,
+    /// Select the match like Return, but keep anyrun open and collect the output of plugins like
+    /// stdin instead of printing it, for picking multiple matches
+    Accept,
+    /// Print the output collected with `Accept` separated by newlines and close
+    Close,
+    /// Sort the matches of the selected match's plugin by the next sort mode it supports
+    CycleSortMode,
+}
+
 // Could have a better name
 #[derive(Deserialize, Clone)]
 enum RelativeNum {
@@ -137,12 +393,92 @@ impl From<&str> for RelativeNum {
     }
 }
 
+/// The width of the runner, either fixed like a `RelativeNum` or fitting the widest match
+#[derive(Deserialize, Clone)]
+enum Width {
+    Absolute(i32),
+    Fraction(f32),
+    /// Shrink the runner to fit the widest match, up to `max`
+    Auto {
+        max: RelativeNum,
+    },
+}
+
+impl Width {
+    /// The fixed width, or the maximum width if it fits the matches
+    fn to_val(&self, val: u32) -> i32 {
+        match self {
+            Width::Absolute(num) => *num,
+            Width::Fraction(frac) => (frac * val as f32) as i32,
+            Width::Auto { max } => max.to_val(val),
+        }
+    }
+}
+
+impl From<&str> for Width {
+    fn from(value: &str) -> Self {
+        // `auto:<max>`, where the maximum is a `RelativeNum`
+        match value.strip_prefix("auto:") {
+            Some(max) => Self::Auto { max: max.into() },
+            None => match RelativeNum::from(value) {
+                RelativeNum::Absolute(num) => Self::Absolute(num),
+                RelativeNum::Fraction(frac) => Self::Fraction(frac),
+            },
+        }
+    }
+}
+
 /// A "view" of plugin's info and matches
 #[derive(Clone)]
 struct PluginView {
     plugin: PluginRef,
     row: gtk::ListBoxRow,
     list: gtk::ListBox,
+    /// Required binaries of the plugin that could not be found
+    missing: Vec<String>,
+    /// The scrollable region around the list, if the amount of visible matches is limited
+    scroll: Option<gtk::ScrolledWindow>,
+    max_visible: Option<usize>,
+    /// The most matches shown from the plugin
+    max_entries: Option<usize>,
+    copy_format: Option<String>,
+    /// The size of the icons of the matches
+    icon_size: i32,
+    /// The icon theme the icons of the matches are loaded from, if it is not the default one
+    icon_theme: Option<gtk::IconTheme>,
+    /// The status message reported by the plugin
+    status: gtk::Label,
+    keybinds: Vec<PluginKeybind>,
+    /// The order the plugin sorts its matches in, shared by the clones of the view
+    sort_mode: Rc<Cell<SortMode>>,
+}
+
+impl PluginView {
+    /// Whether the row is one of the plugin's matches
+    fn owns(&self, row: &gtk::ListBoxRow) -> bool {
+        // The row of the plugin is stored as `plugin` on the rows of its matches
+        unsafe {
+            row.data::<gtk::ListBoxRow>("plugin")
+                .map_or(false, |plugin| *plugin.as_ref() == self.row)
+        }
+    }
+
+    /// The rows of the plugin's matches. The plugins share their list when they are combined into a
+    /// single one, which then contains the matches of the other plugins as well.
+    fn rows(&self) -> Vec<gtk::ListBoxRow> {
+        self.list
+            .children()
+            .into_iter()
+            // All children of lists are GtkListBoxRow widgets
+            .map(|child| child.downcast::<gtk::ListBoxRow>().unwrap())
+            .filter(|row| self.owns(row))
+            .collect()
+    }
+
+    /// The selected match, if it is one of the plugin's
+    fn selected_row(&self) -> Option<gtk::ListBoxRow> {
+        self.list.selected_row().filter(|row| self.owns(row))
+    }
 }
 
 #[derive(Parser)]
@@ -150,8 +486,39 @@ struct Args {
     /// Override the path to the config directory
     #[arg(short, long)]
     config_dir: Option<String>,
+    /// Read the config in RON format from stdin instead of `config.ron`. The config ends at a
+    /// line containing only `---` or a NUL byte, the rest of stdin is left for plugins reading it.
+    #[arg(long)]
+    config_stdin: bool,
+    /// Print the selected match as JSON to stdout when anyrun closes
+    #[arg(long)]
+    print_selection: bool,
+    /// Start in the exclusive mode of the plugin with this name, showing only its matches
+    #[arg(long)]
+    plugin: Option<String>,
+    /// Don't load the plugin with this name for this run, can be given multiple times
+    #[arg(long, value_name = "NAME")]
+    exclude: Vec<String>,
+    /// Show the score of each match after its description, for plugins that provide it
+    #[arg(long, hide = true)]
+    debug_scores: bool,
     #[command(flatten)]
     config: ConfigArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print timings of loading the config, loading and initializing the plugins and getting
+    /// the first matches for a query as JSON
+    Bench {
+        /// The query to get the first matches for
+        #[arg(default_value = "a")]
+        query: String,
+    },
+    /// Print the widget names and CSS classes that can be used for styling
+    StyleNames,
 }
 
 #[derive(Deserialize, Clone, ValueEnum)]
@@ -163,6 +530,8 @@ enum Position {
 /// Actions to run after GTK has finished
 enum PostRunAction {
     Copy(Vec<u8>),
+    /// Print the input anyrun was closed with, for `query_on_escape`
+    Query(String),
     None,
 }
 
@@ -172,10 +541,66 @@ struct RuntimeData {
     exclusive: Option<PluginView>,
     plugins: Vec<PluginView>,
     post_run_action: PostRunAction,
+    /// The output of the matches selected with the `Accept` action, printed by the `Close` action
+    accepted: Vec<Vec<u8>>,
     config: Config,
     /// Used for displaying errors later on
     error_label: String,
     config_dir: String,
+    /// Label showing the only match next to the entry, if `inline_first_result` is enabled
+    inline_label: Option<gtk::Label>,
+    /// Label showing the total amount of matches, if `show_result_count` is enabled
+    result_count_label: Option<gtk::Label>,
+    /// Whether the user has moved the selection since the input last changed
+    navigated: bool,
+    /// The amount of plugins with matches that are shown, if `collapse_plugins` is enabled
+    expanded_plugins: usize,
+    /// The input before a plugin entered exclusive mode, restored when it exits it
+    pre_exclusive_input: Option<String>,
+    /// The latest input matches were requested for
+    query: String,
+    /// Whether matches were requested at all yet, plugins are only queried again if they were
+    queried: bool,
+    match_cache: RefCell<MatchCache>,
+    /// Whether to print the selected match as JSON when closing
+    print_selection: bool,
+    /// The selected match to print
+    selection: Option<serde_json::Value>,
+    /// Whether to show the scores of the matches
+    debug_scores: bool,
+    /// The name of the plugin to start in the exclusive mode of
+    start_plugin: Option<String>,
+    /// The names of the plugins that are not loaded
+    exclude: Vec<String>,
+    /// The main box and what is needed to resize it, if the `width` is `Auto`
+    auto_width: Option<AutoWidth>,
+    /// The list the matches of all plugins are shown in, if `combine_mode` or `global_sort`
+    /// combines them into one
+    combined_list: Option<gtk::ListBox>,
+    /// The list containing the fallback match and its title, if a `fallback` is configured
+    fallback_list: Option<(gtk::ListBox, gtk::Label)>,
+    /// The amount of plugins that haven't returned their matches for the input yet
+    pending: Cell<usize>,
+    /// The amount of pages of `max_entries` matches that are shown, if `paginate` is enabled
+    pages: usize,
+    /// The list containing the "Show more" row, if `paginate` is enabled
+    more_list: Option<gtk::ListBox>,
+    /// Whether matches for the input were left out due to `max_entries`
+    truncated: Cell<bool>,
+    /// The plugin name, ID and title of the match to select again after its plugin refreshed the
+    /// matches, if `reselect_on_refresh` is enabled
+    reselect: RefCell<Option<(RString, ROption<u64>, RString)>>,
+}
+
+/// For resizing the main box to fit the matches when the `width` is `Auto`
+struct AutoWidth {
+    fixed: gtk::Fixed,
+    main_vbox: gtk::Box,
+    /// The horizontal center of the main box
+    center: i32,
+    y: i32,
+    /// The `max` of the `width`, which the main box is limited to
+    max_width: i32,
 }
 
 /// The naming scheme for CSS styling
@@ -196,23 +621,166 @@ mod style_names {
 
     pub const MATCH_TITLE: &str = "match-title";
     pub const MATCH_DESC: &str = "match-desc";
+    pub const MATCH_BADGE: &str = "match-badge";
+    pub const MATCH_HINT: &str = "match-hint";
+    pub const MATCH_IMAGE: &str = "match-image";
+    pub const MATCH_INDEX: &str = "match-index";
+
+    /// The warning shown under a plugin if it is missing required binaries
+    pub const PLUGIN_WARNING: &str = "plugin-warning";
+    /// The status message a plugin reports, shown under its info
+    pub const PLUGIN_STATUS: &str = "plugin-status";
+    /// The separator between plugins when `plugin_separator` is enabled
+    pub const PLUGIN_SEPARATOR: &str = "plugin-separator";
+    /// CSS class of the entry when the input starts with the prefix of a plugin
+    pub const PREFIX_ACTIVE: &str = "prefix-active";
+    /// The only match shown next to the entry when `inline_first_result` is enabled
+    pub const INLINE_RESULT: &str = "inline-result";
+    /// The total amount of matches when `show_result_count` is enabled
+    pub const RESULT_COUNT: &str = "result-count";
+
+    /// The widget names with the widgets they are used for and a description, printed by
+    /// `anyrun style-names`. New names need to be added here as well.
+    pub const NAMES: &[(&str, &str, &str)] = &[
+        (ENTRY, "GtkEntry", "The entry box"),
+        (
+            WINDOW,
+            "GtkWindow",
+            "The window, unless another name is set with `window_name`",
+        ),
+        (
+            MAIN,
+            "GtkListBox, GtkBox",
+            "The main list containing the plugins and the box combining it with the entry",
+        ),
+        (
+            PLUGIN,
+            "GtkListBoxRow, GtkBox, GtkLabel, GtkImage, GtkSeparator, GtkListBox, GtkScrolledWindow",
+            "Anything for the entire plugin, like its name, icon and list of matches",
+        ),
+        (
+            MATCH,
+            "GtkListBoxRow, GtkBox, GtkImage",
+            "The row of a match, its boxes and its icon",
+        ),
+        (MATCH_TITLE, "GtkLabel", "The title of a match"),
+        (MATCH_DESC, "GtkLabel", "The description of a match"),
+        (MATCH_BADGE, "GtkLabel", "The badge on the right side of a match"),
+        (MATCH_HINT, "GtkLabel", "The hint on the right side of a match"),
+        (
+            MATCH_IMAGE,
+            "GtkImage",
+            "The image of a match, shown instead of its icon",
+        ),
+        (
+            MATCH_INDEX,
+            "GtkLabel",
+            "The number of a match when `quick_select` is enabled",
+        ),
+        (
+            PLUGIN_WARNING,
+            "GtkLabel",
+            "The warning shown under a plugin if binaries it requires are missing",
+        ),
+        (
+            PLUGIN_STATUS,
+            "GtkLabel",
+            "The status message a plugin reported",
+        ),
+        (
+            PLUGIN_SEPARATOR,
+            "GtkSeparator",
+            "The separator between plugins when `plugin_separator` is enabled",
+        ),
+        (
+            INLINE_RESULT,
+            "GtkLabel",
+            "The match shown next to the entry when `inline_first_result` is enabled",
+        ),
+        (
+            RESULT_COUNT,
+            "GtkLabel",
+            "The total amount of matches when `show_result_count` is enabled",
+        ),
+    ];
+
+    /// The CSS classes in the same format as `NAMES`
+    pub const CLASSES: &[(&str, &str, &str)] = &[(
+        PREFIX_ACTIVE,
+        "GtkEntry",
+        "The entry when the input starts with the prefix of a plugin",
+    )];
+}
+
+/// Read the config passed with `--config-stdin`, which ends at a line containing only `---`, a NUL
+/// byte or the end of the input. The input is read one byte at a time so that nothing after the
+/// delimiter is consumed, leaving it for plugins that read stdin.
+fn read_stdin_config(mut input: impl Read) -> io::Result<String> {
+    let mut content = Vec::new();
+    let mut line_start = 0;
+    let mut byte = [0];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => break,
+            Ok(_) => (),
+            Err(why) if why.kind() == io::ErrorKind::Interrupted => continue,
+            Err(why) => return Err(why),
+        }
+        match byte[0] {
+            0 => break,
+            b'\n' if &content[line_start..] == b"---" => {
+                content.truncate(line_start);
+                break;
+            }
+            b'\n' => {
+                content.push(b'\n');
+                line_start = content.len();
+            }
+            byte => content.push(byte),
+        }
+    }
+    // A delimiter without a trailing newline at the end of the input
+    if &content[line_start..] == b"---" {
+        content.truncate(line_start);
+    }
+    String::from_utf8(content).map_err(|why| io::Error::new(io::ErrorKind::InvalidData, why))
+}
+
+/// Print the selectors that can be used in `style.css`, with the widgets they apply to
+fn print_style_names() {
+    let selectors = style_names::NAMES
+        .iter()
+        .map(|(name, widgets, description)| (format!("#{}", name), widgets, description))
+        .chain(
+            style_names::CLASSES
+                .iter()
+                .map(|(class, widgets, description)| (format!(".{}", class), widgets, description)),
+        )
+        .collect::<Vec<_>>();
+
+    let width = selectors
+        .iter()
+        .map(|(selector, _, _)| selector.len())
+        .max()
+        .unwrap_or(0);
+    for (selector, widgets, description) in selectors {
+        println!("{:<width$}  {}", selector, description, width = width);
+        println!("{:<width$}  Widgets: {}", "", widgets, width = width);
+    }
 }
 
 /// Default config directory
 pub const DEFAULT_CONFIG_DIR: &str = "/etc/anyrun";
 
 fn main() {
-    let app = gtk::Application::new(Some("com.kirottu.anyrun"), Default::default());
-
-    // Register here so we know if the instance is the primary or a remote
-    app.register(None::<&gio::Cancellable>).unwrap();
+    let args = Args::parse();
 
-    // If another instance is running, quit
-    if app.is_remote() {
+    if let Some(Command::StyleNames) = args.command {
+        print_style_names();
         return;
     }
 
-    let args = Args::parse();
+    let config_start = Instant::now();
 
     // Figure out the config dir
     let user_dir = format!(
@@ -228,7 +796,15 @@ fn main() {
     });
 
     // Load config, if unable to then read default config. If an error occurs the message will be displayed.
-    let (mut config, error_label) = match fs::read_to_string(format!("{}/config.ron", config_dir)) {
+    let content = if args.config_stdin {
+        // Read straight from the file descriptor, `io::stdin()` would buffer past the delimiter.
+        // It's wrapped in `ManuallyDrop` so that stdin isn't closed for the plugins.
+        let stdin = mem::ManuallyDrop::new(unsafe { fs::File::from_raw_fd(0) });
+        read_stdin_config(&*stdin)
+    } else {
+        fs::read_to_string(format!("{}/config.ron", config_dir))
+    };
+    let (mut config, error_label) = match content {
         Ok(content) => ron::from_str(&content)
             .map(|config| (config, String::new()))
             .unwrap_or_else(|why| {
@@ -251,13 +827,58 @@ fn main() {
 
     config.merge_opt(args.config);
 
+    // Plugins run matches in a terminal with `$TERMINAL`
+    if let Some(terminal) = &config.terminal {
+        env::set_var("TERMINAL", terminal);
+    }
+
+    if let Some(Command::Bench { query }) = args.command {
+        if !error_label.is_empty() {
+            eprintln!("{}", error_label);
+        }
+        bench(config, config_dir, config_start.elapsed(), query);
+        return;
+    }
+
+    let app = gtk::Application::new(Some("com.kirottu.anyrun"), Default::default());
+
+    // Register here so we know if the instance is the primary or a remote
+    app.register(None::<&gio::Cancellable>).unwrap();
+
+    // If another instance is running, quit
+    if app.is_remote() {
+        return;
+    }
+
     let runtime_data: Rc<RefCell<RuntimeData>> = Rc::new(RefCell::new(RuntimeData {
         exclusive: None,
         plugins: Vec::new(),
         post_run_action: PostRunAction::None,
-        config,
+        accepted: Vec::new(),
         error_label,
         config_dir,
+        inline_label: None,
+        result_count_label: None,
+        navigated: false,
+        expanded_plugins: 1,
+        pre_exclusive_input: None,
+        query: String::new(),
+        queried: false,
+        match_cache: RefCell::new(MatchCache::new(config.match_cache_size)),
+        print_selection: args.print_selection,
+        debug_scores: args.debug_scores,
+        start_plugin: args.plugin,
+        exclude: args.exclude,
+        auto_width: None,
+        combined_list: None,
+        fallback_list: None,
+        pending: Cell::new(0),
+        pages: 1,
+        more_list: None,
+        truncated: Cell::new(false),
+        reselect: RefCell::new(None),
+        selection: None,
+        config,
     }));
 
     let runtime_data_clone = runtime_data.clone();
@@ -268,6 +889,10 @@ fn main() {
 
     let runtime_data = runtime_data.borrow_mut();
 
+    if let Some(selection) = &runtime_data.selection {
+        println!("{}", selection);
+    }
+
     // Perform a post run action if one is set
     match &runtime_data.post_run_action {
         PostRunAction::Copy(bytes) => match unsafe { unistd::fork() } {
@@ -277,10 +902,30 @@ fn main() {
             }
             // Child process starts serving copy requests
             Ok(unistd::ForkResult::Child) => {
+                // Trailing whitespace is only trimmed from text, other content is copied as is
+                let bytes = match std::str::from_utf8(bytes) {
+                    Ok(text) if runtime_data.config.trim_copy => text
+                        .lines()
+                        .map(str::trim_end)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                        .trim_end()
+                        .as_bytes()
+                        .to_vec(),
+                    _ => bytes.clone(),
+                };
+
+                if runtime_data.config.record_copy_history {
+                    record_copy(&bytes);
+                }
+
                 let mut opts = copy::Options::new();
                 opts.foreground(true);
+                if runtime_data.config.copy_to_primary {
+                    opts.clipboard(copy::ClipboardType::Both);
+                }
                 opts.copy(
-                    copy::Source::Bytes(bytes.clone().into_boxed_slice()),
+                    copy::Source::Bytes(bytes.into_boxed_slice()),
                     copy::MimeType::Autodetect,
                 )
                 .expect("Failed to serve copy bytes");
@@ -289,15 +934,64 @@ fn main() {
                 eprintln!("Failed to fork for copy sharing: {}", why);
             }
         },
+        // The marker tells the input apart from the output of a selected match
+        PostRunAction::Query(query) => println!("query:{}", query),
         PostRunAction::None => (),
     }
 }
 
+/// The amount of the most recent copies kept in the copy history
+const COPY_HISTORY_SIZE: usize = 1000;
+
+/// Append the copied text to the copy history in `$XDG_CACHE_HOME/anyrun/copy-history.jsonl`,
+/// which has one JSON string per line with the most recent copy last. Other content is not
+/// recorded.
+fn record_copy(bytes: &[u8]) {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+    let dir = env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(format!("{}/.cache", env::var("HOME").unwrap_or_default()))
+        })
+        .join("anyrun");
+    let path = dir.join("copy-history.jsonl");
+
+    let mut lines = fs::read_to_string(&path)
+        .map(|content| content.lines().map(str::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    lines.push(serde_json::Value::from(text).to_string());
+    let skip = lines.len().saturating_sub(COPY_HISTORY_SIZE);
+
+    if let Err(why) =
+        fs::create_dir_all(&dir).and_then(|_| fs::write(&path, lines[skip..].join("\n") + "\n"))
+    {
+        eprintln!("Failed to record the copy history: {}", why);
+    }
+}
+
 fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
+    // Widgets use the default direction unless it is set for them, GTK sets it from the locale
+    match runtime_data.borrow().config.text_direction {
+        TextDirection::Auto => (),
+        TextDirection::Ltr => gtk::Widget::set_default_direction(gtk::TextDirection::Ltr),
+        TextDirection::Rtl => gtk::Widget::set_default_direction(gtk::TextDirection::Rtl),
+    }
+
+    // The name of the window can be changed for styling it with a more specific selector
+    let window_name = runtime_data
+        .borrow()
+        .config
+        .window_name
+        .clone()
+        .unwrap_or_else(|| style_names::WINDOW.to_string());
+
     // Create the main window
     let window = gtk::ApplicationWindow::builder()
         .application(app)
-        .name(style_names::WINDOW)
+        .name(&window_name)
         .build();
 
     // Init GTK layer shell
@@ -342,60 +1036,113 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
 
+    // Dim the rest of the screen, which is covered by the window, when clicking it closes anyrun
+    if let (true, Some(dim)) = (
+        runtime_data.borrow().config.close_on_click,
+        runtime_data.borrow().config.background_dim,
+    ) {
+        let dim_provider = gtk::CssProvider::new();
+        dim_provider
+            .load_from_data(
+                format!(
+                    "#{} {{ background-color: rgba(0, 0, 0, {}); }}",
+                    window_name,
+                    dim.clamp(0.0, 1.0)
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        // Takes priority over `style.css`, as the option is set explicitly
+        window
+            .style_context()
+            .add_provider(&dim_provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+    }
+
     // Create the main list of plugin views
     let main_list = gtk::ListBox::builder()
         .selection_mode(gtk::SelectionMode::None)
         .name(style_names::MAIN)
         .build();
 
-    // Prioritise the ANYRUN_PLUGINS env var over other paths
-    let mut plugin_paths = match env::var("ANYRUN_PLUGINS") {
-        Ok(string) => string.split(':').map(PathBuf::from).collect::<Vec<_>>(),
-        Err(_) => Vec::new(),
-    };
+    // Put a separator above every visible plugin except the first one
+    if runtime_data.borrow().config.plugin_separator {
+        main_list.set_header_func(Some(Box::new(|row, before| {
+            if before.is_none() {
+                row.set_header(None::<&gtk::Widget>);
+            } else if row.header().is_none() {
+                let separator = gtk::Separator::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .name(style_names::PLUGIN_SEPARATOR)
+                    .build();
+                separator.show();
+                row.set_header(Some(&separator));
+            }
+        })));
+    }
 
-    plugin_paths.append(&mut vec![
-        format!("{}/plugins", runtime_data.borrow().config_dir).into(),
-        format!("{}/plugins", DEFAULT_CONFIG_DIR).into(),
-    ]);
+    // With `RoundRobin` or `global_sort` the matches of all plugins are shown in a single list
+    // shared by the plugins
+    let combine_mode = runtime_data.borrow().config.combine_mode;
+    let global_sort = runtime_data.borrow().config.global_sort;
+    let combined_list = (combine_mode == CombineMode::RoundRobin || global_sort).then(|| {
+        let list = gtk::ListBox::builder()
+            .name(style_names::PLUGIN)
+            .hexpand(true)
+            .build();
+        list.set_sort_func(Some(Box::new(move |a, b| {
+            // The plugin row, the position among the plugin's matches and the match are stored on
+            // the rows
+            let key = |row: &gtk::ListBoxRow| unsafe {
+                let plugin = row
+                    .data::<gtk::ListBoxRow>("plugin")
+                    .map_or(0, |plugin| plugin.as_ref().index() as usize);
+                let index = row
+                    .data::<usize>("index")
+                    .map_or(0, |index| *index.as_ptr());
+                let score = row
+                    .data::<Match>("match")
+                    .and_then(|_match| _match.as_ref().score.into_option());
+                combined_key(combine_mode, global_sort, plugin, index, score)
+            };
+            match key(a).cmp(&key(b)) {
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+                Ordering::Greater => 1,
+            }
+        })));
+        list
+    });
+    runtime_data.borrow_mut().combined_list = combined_list.clone();
+
+    let plugin_paths = plugin_paths(&runtime_data.borrow().config_dir);
+    let exclude = runtime_data.borrow().exclude.clone();
 
     // Load plugins from the paths specified in the config file
-    let plugins = runtime_data
+    let mut plugins = runtime_data
         .borrow()
         .config
         .plugins
         .iter()
-        .map(|plugin_path| {
-            // Load the plugin's dynamic library.
-            let mut user_path =
-                PathBuf::from(&format!("{}/plugins", runtime_data.borrow().config_dir));
-            let mut global_path = PathBuf::from("/etc/anyrun/plugins");
-            user_path.extend(plugin_path.iter());
-            global_path.extend(plugin_path.iter());
-
-            // Load the plugin's dynamic library.
-
-            let plugin = if plugin_path.is_absolute() {
-                abi_stable::library::lib_header_from_path(plugin_path)
-            } else {
-                let path = plugin_paths
-                    .clone()
-                    .into_iter()
-                    .map(|mut path| {
-                        path.push(plugin_path);
-                        path
-                    })
-                    .find(|path| path.exists())
-                    .expect("Invalid plugin path");
-
-                abi_stable::library::lib_header_from_path(&path)
-            }
-            .and_then(|plugin| plugin.init_root_module::<PluginRef>())
-            .expect("Failed to load plugin");
-
+        .map(|plugin_path| (plugin_path, load_plugin(plugin_path, &plugin_paths)))
+        // Leave out the excluded plugins before they are initialized
+        .filter(|(_, plugin)| {
+            let name = plugin.info()().name;
+            !exclude
+                .iter()
+                .any(|excluded| name.eq_ignore_ascii_case(excluded))
+        })
+        .map(|(plugin_path, plugin)| {
             // Run the plugin's init code to init static resources etc.
             plugin.init()(runtime_data.borrow().config_dir.clone().into());
 
+            let info = plugin.info()();
+            let missing = info
+                .requires
+                .iter()
+                .filter(|binary| !binary_exists(binary))
+                .map(|binary| binary.to_string())
+                .collect::<Vec<_>>();
+
             let plugin_box = gtk::Box::builder()
                 .orientation(gtk::Orientation::Horizontal)
                 .spacing(10)
@@ -403,7 +1150,7 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
                 .build();
             if !runtime_data.borrow().config.hide_plugin_info {
                 plugin_box.add(&create_info_box(
-                    &plugin.info()(),
+                    &info,
                     runtime_data.borrow().config.hide_icons,
                 ));
                 plugin_box.add(
@@ -413,29 +1160,178 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
                         .build(),
                 );
             }
-            let list = gtk::ListBox::builder()
+            // Box for the warning about missing binaries and the list of matches
+            let list_box = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
                 .name(style_names::PLUGIN)
                 .hexpand(true)
                 .build();
-
-            plugin_box.add(&list);
+            if !missing.is_empty() {
+                list_box.add(
+                    &gtk::Label::builder()
+                        .label(&format!(
+                            "Missing required binaries: {}",
+                            missing.join(", ")
+                        ))
+                        .name(style_names::PLUGIN_WARNING)
+                        .wrap(true)
+                        .xalign(0.0)
+                        .halign(gtk::Align::Start)
+                        .build(),
+                );
+            }
+            // Hidden until the plugin reports a status
+            let status = gtk::Label::builder()
+                .name(style_names::PLUGIN_STATUS)
+                .wrap(true)
+                .xalign(0.0)
+                .halign(gtk::Align::Start)
+                .no_show_all(true)
+                .build();
+            list_box.add(&status);
+            let list = match &combined_list {
+                Some(combined_list) => combined_list.clone(),
+                None => gtk::ListBox::builder()
+                    .name(style_names::PLUGIN)
+                    .hexpand(true)
+                    .build(),
+            };
+
+            let settings = runtime_data
+                .borrow()
+                .config
+                .plugin_settings
+                .get(plugin_path)
+                .cloned()
+                .unwrap_or_default();
+
+            // Limit the height of the list by wrapping it in a scrollable region. The combined list
+            // isn't part of the plugin's row, so it only shows the warning and the status.
+            let scroll = settings
+                .max_visible
+                .filter(|_| combined_list.is_none())
+                .map(|_| {
+                    gtk::ScrolledWindow::builder()
+                        .name(style_names::PLUGIN)
+                        .hscrollbar_policy(gtk::PolicyType::Never)
+                        .propagate_natural_height(true)
+                        .propagate_natural_width(true)
+                        .build()
+                });
+            match (&scroll, &combined_list) {
+                (_, Some(_)) => (),
+                (Some(scroll), None) => {
+                    scroll.add(&list);
+                    list_box.add(scroll);
+                }
+                (None, None) => list_box.add(&list),
+            }
+            plugin_box.add(&list_box);
 
             let row = gtk::ListBoxRow::builder().name(style_names::PLUGIN).build();
             row.add(&plugin_box);
 
-            main_list.add(&row);
-
-            PluginView { plugin, row, list }
+            (
+                plugin_path.clone(),
+                PluginView {
+                    plugin,
+                    row,
+                    list,
+                    missing,
+                    scroll,
+                    max_visible: settings.max_visible,
+                    max_entries: settings.max_entries,
+                    copy_format: settings.copy_format.clone(),
+                    icon_size: settings
+                        .icon_size
+                        .unwrap_or(runtime_data.borrow().config.icon_size),
+                    icon_theme: settings.icon_theme.as_ref().map(|name| {
+                        let icon_theme = gtk::IconTheme::new();
+                        icon_theme.set_custom_theme(Some(name));
+                        icon_theme
+                    }),
+                    status,
+                    keybinds: info.keybinds.to_vec(),
+                    sort_mode: Rc::new(Cell::new(match settings.sort_mode {
+                        Some(sort_mode) if info.sort_modes.contains(&sort_mode) => sort_mode,
+                        Some(sort_mode) => {
+                            eprintln!(
+                                "{} doesn't support sorting by {:?}, using its default",
+                                info.name, sort_mode
+                            );
+                            default_sort_mode(&info)
+                        }
+                        None => default_sort_mode(&info),
+                    })),
+                },
+            )
         })
+        .collect::<Vec<(PathBuf, PluginView)>>();
+
+    // Sort the plugins by `display_order`, the ones not in it are shown after in the load order
+    let display_order = runtime_data.borrow().config.display_order.clone();
+    plugins.sort_by_key(|(plugin_path, _)| {
+        display_order
+            .iter()
+            .position(|path| path == plugin_path)
+            .unwrap_or(display_order.len())
+    });
+    let plugins = plugins
+        .into_iter()
+        .map(|(_, plugin_view)| plugin_view)
         .collect::<Vec<PluginView>>();
 
+    for plugin_view in &plugins {
+        main_list.add(&plugin_view.row);
+    }
+
+    // Start in the exclusive mode of the requested plugin
+    let start_plugin = runtime_data.borrow().start_plugin.clone();
+    if let Some(name) = start_plugin {
+        let plugin_view = plugins
+            .iter()
+            .find(|view| view.plugin.info()().name.eq_ignore_ascii_case(&name))
+            .cloned();
+        if plugin_view.is_none() {
+            eprintln!(
+                "No plugin named {} is loaded, available plugins: {}",
+                name,
+                plugins
+                    .iter()
+                    .map(|view| view.plugin.info()().name.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        runtime_data.borrow_mut().exclusive = plugin_view;
+    }
+
     // Assign the plugins here to avoid multiple mutable/immutable borrows
     runtime_data.borrow_mut().plugins = plugins;
 
     // Connect selection events to avoid completely messing up selection logic
-    for plugin_view in runtime_data.borrow().plugins.iter() {
+    for plugin_view in connected_views(&runtime_data.borrow()) {
         let plugins_clone = runtime_data.borrow().plugins.clone();
+        let scroll = plugin_view.scroll.clone();
+        let description_on_select = runtime_data.borrow().config.description_on_select;
         plugin_view.list.connect_row_selected(move |list, row| {
+            // Only show the description of the selected match, the other lists hide theirs when
+            // they are unselected below
+            if description_on_select {
+                for child in list.children() {
+                    set_description_visible(&child, row.map_or(false, |row| *row == child));
+                }
+            }
+
+            // Keep the selected row visible in a scrollable list
+            if let (Some(row), Some(scroll)) = (row, &scroll) {
+                let allocation = row.allocation();
+                scroll.vadjustment().clamp_page(
+                    allocation.y() as f64,
+                    (allocation.y() + allocation.height()) as f64,
+                );
+            }
+
             if row.is_some() {
                 let combined_matches = plugins_clone
                     .iter()
@@ -464,12 +1360,200 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
         .hexpand(true)
         .name(style_names::ENTRY)
         .build();
+    // Shown while the entry is empty
+    entry.set_placeholder_text(runtime_data.borrow().config.placeholder.as_deref());
+
+    if runtime_data.borrow().config.inline_first_result {
+        runtime_data.borrow_mut().inline_label = Some(
+            gtk::Label::builder()
+                .name(style_names::INLINE_RESULT)
+                .no_show_all(true)
+                .ellipsize(pango::EllipsizeMode::End)
+                .build(),
+        );
+    }
 
-    // Refresh the matches when text input changes
-    let runtime_data_clone = runtime_data.clone();
-    entry.connect_changed(move |entry| {
-        refresh_matches(entry.text().to_string(), runtime_data_clone.clone())
-    });
+    if runtime_data.borrow().config.show_result_count {
+        runtime_data.borrow_mut().result_count_label = Some(
+            gtk::Label::builder()
+                .name(style_names::RESULT_COUNT)
+                .halign(gtk::Align::End)
+                .no_show_all(true)
+                .build(),
+        );
+    }
+
+    if runtime_data.borrow().config.fallback.is_some() {
+        let label = gtk::Label::builder()
+            .name(style_names::MATCH_TITLE)
+            .halign(gtk::Align::Start)
+            .ellipsize(pango::EllipsizeMode::End)
+            .build();
+        let row = gtk::ListBoxRow::builder()
+            .name(style_names::MATCH)
+            .height_request(32)
+            .build();
+        row.add(&label);
+        let list = gtk::ListBox::builder()
+            .name(style_names::PLUGIN)
+            .no_show_all(true)
+            .build();
+        list.add(&row);
+        // The list itself is only shown when the fallback is needed
+        row.show_all();
+
+        let window = window.clone();
+        let runtime_data_clone = runtime_data.clone();
+        list.connect_row_activated(move |_, _| {
+            if run_fallback(&runtime_data_clone.borrow()) {
+                window.close();
+            }
+        });
+        runtime_data.borrow_mut().fallback_list = Some((list, label));
+    }
+
+    if runtime_data.borrow().config.paginate {
+        let row = gtk::ListBoxRow::builder()
+            .name(style_names::MATCH)
+            .height_request(32)
+            .build();
+        row.add(
+            &gtk::Label::builder()
+                .name(style_names::MATCH_TITLE)
+                .halign(gtk::Align::Start)
+                .label("Show more")
+                .build(),
+        );
+        let list = gtk::ListBox::builder()
+            .name(style_names::PLUGIN)
+            .no_show_all(true)
+            .build();
+        list.add(&row);
+        // The list itself is only shown when there are more matches
+        row.show_all();
+
+        let runtime_data_clone = runtime_data.clone();
+        list.connect_row_activated(move |_, _| show_more(&runtime_data_clone));
+        runtime_data.borrow_mut().more_list = Some(list);
+    }
+
+    // When the user last typed or pressed a key
+    let last_activity = Rc::new(Cell::new(Instant::now()));
+
+    // Close the window once there has been no input for `idle_timeout_secs`
+    if let Some(timeout) = runtime_data.borrow().config.idle_timeout_secs {
+        let window = window.clone();
+        let last_activity = last_activity.clone();
+        glib::timeout_add_seconds_local(1, move || {
+            if last_activity.get().elapsed() >= Duration::from_secs(timeout) {
+                window.close();
+                glib::Continue(false)
+            } else {
+                glib::Continue(true)
+            }
+        });
+    }
+
+    // Query plugins again once they ask for it, like after loading their data in the background
+    let runtime_data_clone = runtime_data.clone();
+    glib::timeout_add_local(Duration::from_millis(100), move || {
+        refresh_requested(&runtime_data_clone);
+        glib::Continue(true)
+    });
+
+    // Refresh the matches when text input changes
+    let runtime_data_clone = runtime_data.clone();
+    let last_activity_clone = last_activity.clone();
+    // Set while an alias is being expanded so that the expansion itself is never expanded
+    let expanding = Cell::new(false);
+    let max_input_length = runtime_data.borrow().config.max_input_length;
+    let input_filter = runtime_data
+        .borrow()
+        .config
+        .input_filter
+        .as_ref()
+        .and_then(|filter| match Regex::new(filter) {
+            Ok(regex) => Some(regex),
+            Err(why) => {
+                eprintln!("Invalid input_filter, not filtering the input: {}", why);
+                None
+            }
+        });
+    // The last input that was allowed, restored if an edit makes the input invalid
+    let last_input = RefCell::new(String::new());
+    // The query waiting for the input to settle, replaced when the input changes again
+    let pending_query: Rc<Cell<Option<glib::SourceId>>> = Rc::new(Cell::new(None));
+    entry.connect_changed(move |entry| {
+        last_activity_clone.set(Instant::now());
+
+        let text = entry.text();
+        let length = text.chars().count();
+        if max_input_length.map_or(false, |max| length > max)
+            || input_filter.as_ref().map_or(false, |filter| {
+                !text
+                    .chars()
+                    .all(|chr| filter.is_match(chr.encode_utf8(&mut [0; 4])))
+            })
+        {
+            // Keep the cursor where it was before the edit, as the text is restored
+            let position =
+                entry.position() - (length as i32 - last_input.borrow().chars().count() as i32);
+            let last_input = last_input.borrow().clone();
+            // This emits the changed signal again with the valid input
+            entry.set_text(&last_input);
+            entry.set_position(position.max(0));
+            return;
+        }
+        *last_input.borrow_mut() = text.to_string();
+
+        // Expand an alias once it is completed with a space
+        if !expanding.get() {
+            let expansion = entry.text().strip_suffix(' ').and_then(|alias| {
+                runtime_data_clone
+                    .borrow()
+                    .config
+                    .aliases
+                    .get(alias)
+                    .cloned()
+            });
+            if let Some(expansion) = expansion {
+                expanding.set(true);
+                // This emits the changed signal again, which refreshes the matches
+                entry.set_text(&expansion);
+                entry.set_position(-1);
+                expanding.set(false);
+                return;
+            }
+        }
+
+        runtime_data_clone.borrow_mut().navigated = false;
+        runtime_data_clone.borrow_mut().expanded_plugins = 1;
+        runtime_data_clone.borrow_mut().pages = 1;
+        runtime_data_clone.borrow().reselect.take();
+        update_active_prefix(entry, &runtime_data_clone.borrow());
+
+        // Only query the plugins once the input hasn't changed for `query_debounce_ms`, so that
+        // typing doesn't send a request to network plugins for every character
+        if let Some(source) = pending_query.take() {
+            source.remove();
+        }
+        let debounce = runtime_data_clone.borrow().config.query_debounce_ms;
+        if debounce == 0 {
+            refresh_matches(entry.text().to_string(), runtime_data_clone.clone());
+        } else {
+            let input = entry.text().to_string();
+            let runtime_data_clone = runtime_data_clone.clone();
+            let pending_query_clone = pending_query.clone();
+            pending_query.set(Some(glib::timeout_add_local_once(
+                Duration::from_millis(debounce),
+                move || {
+                    // The source is removed after running, so it must not be removed again
+                    pending_query_clone.take();
+                    refresh_matches(input, runtime_data_clone);
+                },
+            )));
+        }
+    });
 
     // Handle other key presses for selection control and all other things that may be needed
     let entry_clone = entry.clone();
@@ -477,143 +1561,224 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
 
     window.connect_key_press_event(move |window, event| {
         use gdk::keys::constants;
+
+        last_activity.set(Instant::now());
+
+        // Configured keybinds take priority over the keybinds of plugins, which take priority over
+        // the default ones
+        let action = runtime_data_clone
+            .borrow()
+            .config
+            .keybinds
+            .iter()
+            .find(|keybind| key_matches(event, &keybind.key, keybind.ctrl, keybind.alt))
+            .map(|keybind| keybind.action.clone());
+        if let Some(action) = action {
+            return handle_action(action, window, &entry_clone, runtime_data_clone.clone());
+        }
+
+        // The keybinds of the plugin of the selected match
+        let plugin_keybind = runtime_data_clone
+            .borrow()
+            .plugins
+            .iter()
+            .find(|view| view.selected_row().is_some())
+            .and_then(|view| {
+                view.keybinds
+                    .iter()
+                    .find(|keybind| key_matches(event, &keybind.key, keybind.ctrl, keybind.alt))
+                    .map(|keybind| keybind.id)
+            });
+        if let Some(id) = plugin_keybind {
+            return handle_selection(
+                window,
+                &entry_clone,
+                runtime_data_clone.clone(),
+                false,
+                Some(id),
+                false,
+            );
+        }
+
+        // Activate the match with the number pressed while holding the quick select modifier
+        let quick_select = runtime_data_clone.borrow().config.quick_select;
+        let digit = event
+            .keyval()
+            .to_unicode()
+            .and_then(|chr| chr.to_digit(10))
+            .filter(|digit| *digit > 0);
+        if let (Some(modifier), Some(digit)) = (quick_select, digit) {
+            if event.state().contains(modifier.mask()) {
+                let target = visible_matches(&runtime_data_clone.borrow())
+                    .into_iter()
+                    .nth(digit as usize - 1);
+                // Numbers past the last match do nothing
+                return match target {
+                    Some((row, list)) => {
+                        list.select_row(Some(&row));
+                        handle_selection(
+                            window,
+                            &entry_clone,
+                            runtime_data_clone.clone(),
+                            false,
+                            None,
+                            false,
+                        )
+                    }
+                    None => Inhibit(true),
+                };
+            }
+        }
+
         match event.keyval() {
             // Close window on escape
             constants::Escape => {
+                if runtime_data_clone.borrow().config.query_on_escape {
+                    runtime_data_clone.borrow_mut().post_run_action =
+                        PostRunAction::Query(entry_clone.text().to_string());
+                }
                 window.close();
                 Inhibit(true)
             }
+            // Accept the match shown inline with tab
+            constants::Tab
+                if runtime_data_clone
+                    .borrow()
+                    .inline_label
+                    .as_ref()
+                    .map_or(false, |label| label.is_visible()) =>
+            {
+                handle_selection(
+                    window,
+                    &entry_clone,
+                    runtime_data_clone.clone(),
+                    false,
+                    None,
+                    false,
+                )
+            }
             // Handle selections
             constants::Down | constants::Tab | constants::Up => {
-                // Combine all of the matches into a `Vec` to allow for easier handling of the selection
-                let combined_matches = runtime_data_clone
+                // Complete the entry with the completion of the selected match, if there is one
+                if event.keyval() == constants::Tab {
+                    let completion = selected_completion(&runtime_data_clone.borrow())
+                        .filter(|completion| entry_clone.text() != completion.as_str());
+                    if let Some(completion) = completion {
+                        entry_clone.set_text(&completion);
+                        entry_clone.set_position(-1);
+                        return Inhibit(true);
+                    }
+                }
+
+                runtime_data_clone.borrow_mut().navigated = true;
+
+                // The lists of the matches that can be navigated through. Plugins collapsed by
+                // `collapse_plugins` are hidden but navigable, as selecting one of their matches
+                // expands them. The plugin shown as the inline result is not.
+                let inline_shown = runtime_data_clone
                     .borrow()
-                    .plugins
-                    .iter()
-                    .flat_map(|view| {
-                        view.list.children().into_iter().map(|child| {
-                            (
-                                // All children of lists are GtkListBoxRow widgets
-                                child.dynamic_cast::<gtk::ListBoxRow>().unwrap(),
-                                view.list.clone(),
-                            )
-                        })
-                    })
-                    .collect::<Vec<(gtk::ListBoxRow, gtk::ListBox)>>();
+                    .inline_label
+                    .as_ref()
+                    .map_or(false, |label| label.is_visible());
+                let mut lists = match_lists(&runtime_data_clone.borrow())
+                    .into_iter()
+                    .filter(|(_, visible)| *visible || !inline_shown)
+                    .map(|(list, _)| list)
+                    .collect::<Vec<_>>();
 
-                // Get the selected match
-                let (selected_match, selected_list) =
-                    match runtime_data_clone.borrow().plugins.iter().find_map(|view| {
-                        view.list.selected_row().map(|row| (row, view.list.clone()))
-                    }) {
-                        Some(selected) => selected,
-                        None => {
-                            // If nothing is selected select either the top or bottom match based on the input
-                            if !combined_matches.is_empty() {
-                                match event.keyval() {
-                                    constants::Down | constants::Tab => combined_matches[0]
-                                        .1
-                                        .select_row(Some(&combined_matches[0].0)),
-                                    constants::Up => {
-                                        combined_matches[combined_matches.len() - 1].1.select_row(
-                                            Some(&combined_matches[combined_matches.len() - 1].0),
-                                        )
-                                    }
-                                    _ => unreachable!(),
-                                }
-                            }
-                            return Inhibit(true);
-                        }
-                    };
+                // The "Show more" row can be selected after the last match
+                let more_list = runtime_data_clone
+                    .borrow()
+                    .more_list
+                    .clone()
+                    .filter(|list| list.is_visible());
+                lists.extend(more_list.clone());
 
-                // Clear the previous selection
-                selected_list.select_row(None::<&gtk::ListBoxRow>);
+                // A selection outside of the navigable lists is treated like no selection
+                let counts = lists
+                    .iter()
+                    .map(|list| list.children().len())
+                    .collect::<Vec<_>>();
+                let selected = lists.iter().enumerate().find_map(|(index, list)| {
+                    list.selected_row().map(|row| (index, row.index() as usize))
+                });
+                let next = match navigate(&counts, selected, event.keyval() == constants::Up) {
+                    Some(next) => next,
+                    None => return Inhibit(true),
+                };
 
-                // Get the index of the current selection
-                let index = combined_matches
+                // Clear the previous selection
+                for list in runtime_data_clone
+                    .borrow()
+                    .plugins
                     .iter()
-                    .position(|(row, _)| *row == selected_match)
-                    .unwrap();
-
-                // Move the selection based on the input, loops from top to bottom and vice versa
-                match event.keyval() {
-                    constants::Down | constants::Tab => {
-                        if index < combined_matches.len() - 1 {
-                            combined_matches[index + 1]
-                                .1
-                                .select_row(Some(&combined_matches[index + 1].0));
-                        } else {
-                            combined_matches[0]
-                                .1
-                                .select_row(Some(&combined_matches[0].0));
-                        }
-                    }
-                    constants::Up => {
-                        if index > 0 {
-                            combined_matches[index - 1]
-                                .1
-                                .select_row(Some(&combined_matches[index - 1].0));
-                        } else {
-                            combined_matches[combined_matches.len() - 1]
-                                .1
-                                .select_row(Some(&combined_matches[combined_matches.len() - 1].0));
-                        }
-                    }
-                    _ => unreachable!(),
+                    .map(|view| &view.list)
+                    .chain(more_list.as_ref())
+                {
+                    list.select_row(None::<&gtk::ListBoxRow>);
                 }
 
+                let (list, row) = next;
+                lists[list].select_row(lists[list].row_at_index(row as i32).as_ref());
+
+                expand_to_selection(&mut runtime_data_clone.borrow_mut());
                 Inhibit(true)
             }
             // Handle when the selected match is "activated"
-            constants::Return => {
-                let mut _runtime_data_clone = runtime_data_clone.borrow_mut();
+            // Ctrl+Enter runs the match in a terminal
+            constants::Return => handle_selection(
+                window,
+                &entry_clone,
+                runtime_data_clone.clone(),
+                event.state().contains(gdk::ModifierType::CONTROL_MASK),
+                None,
+                false,
+            ),
+            _ => Inhibit(false),
+        }
+    });
 
-                let (selected_match, plugin_view) = match _runtime_data_clone
-                    .plugins
-                    .iter()
-                    .find_map(|view| view.list.selected_row().map(|row| (row, view)))
-                {
-                    Some(selected) => selected,
-                    None => {
-                        return Inhibit(false);
-                    }
+    // Perform the configured actions when a match is clicked
+    for plugin_view in connected_views(&runtime_data.borrow()) {
+        let window = window.clone();
+        let entry = entry.clone();
+        let runtime_data = runtime_data.clone();
+        plugin_view
+            .list
+            .connect_button_press_event(move |list, event| {
+                let click = match runtime_data.borrow().config.activate_on {
+                    ActivateOn::SingleClick => gdk::EventType::ButtonPress,
+                    ActivateOn::DoubleClick => gdk::EventType::DoubleButtonPress,
                 };
+                if event.event_type() != click {
+                    return Inhibit(false);
+                }
 
-                // Perform actions based on the result of handling the selection
-                match plugin_view.plugin.handle_selection()(unsafe {
-                    (*selected_match.data::<Match>("match").unwrap().as_ptr()).clone()
-                }) {
-                    HandleResult::Close => {
-                        window.close();
-                        Inhibit(true)
-                    }
-                    HandleResult::Refresh(exclusive) => {
-                        if exclusive {
-                            _runtime_data_clone.exclusive = Some(plugin_view.clone());
-                        } else {
-                            _runtime_data_clone.exclusive = None;
-                        }
-                        mem::drop(_runtime_data_clone); // Drop the mutable borrow
-                        refresh_matches(entry_clone.text().into(), runtime_data_clone.clone());
-                        Inhibit(false)
-                    }
-                    HandleResult::Copy(bytes) => {
-                        _runtime_data_clone.post_run_action = PostRunAction::Copy(bytes.into());
-                        window.close();
-                        Inhibit(true)
-                    }
-                    HandleResult::Stdout(bytes) => {
-                        if let Err(why) = io::stdout().lock().write_all(&bytes) {
-                            eprintln!("Error outputting content to stdout: {}", why);
-                        }
-                        window.close();
-                        Inhibit(true)
-                    }
+                let action = match event.button() {
+                    1 => runtime_data.borrow().config.primary_click.clone(),
+                    2 => runtime_data.borrow().config.middle_click.clone(),
+                    3 => runtime_data.borrow().config.secondary_click.clone(),
+                    _ => return Inhibit(false),
+                };
+
+                // The position is relative to the window the event happened in
+                if event.window() != list.window() {
+                    return Inhibit(false);
                 }
-            }
-            _ => Inhibit(false),
-        }
-    });
+
+                match list.row_at_y(event.position().1 as i32) {
+                    Some(row) => handle_mouse_action(
+                        action,
+                        &row,
+                        list,
+                        &window,
+                        &entry,
+                        runtime_data.clone(),
+                    ),
+                    None => Inhibit(false),
+                }
+            });
+    }
 
     // If the option is enabled, close the window when any click is received
     // that is outside the bounds of the main box
@@ -638,6 +1803,7 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
         let main_list = main_list.clone();
 
         configure_once.call_once(move || {
+            let mut auto_width = None;
             {
                 let runtime_data = runtime_data.borrow();
 
@@ -656,7 +1822,19 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
                     .height_request(height)
                     .name(style_names::MAIN)
                     .build();
-                main_vbox.add(&entry);
+                match &runtime_data.inline_label {
+                    Some(inline_label) => {
+                        let entry_box = gtk::Box::builder()
+                            .orientation(gtk::Orientation::Horizontal)
+                            .spacing(10)
+                            .name(style_names::MAIN)
+                            .build();
+                        entry_box.add(&entry);
+                        entry_box.add(inline_label);
+                        main_vbox.add(&entry_box);
+                    }
+                    None => main_vbox.add(&entry),
+                }
 
                 // Display the error message
                 if !runtime_data.error_label.is_empty() {
@@ -671,17 +1849,46 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
                     );
                 }
 
+                if let Some(result_count_label) = &runtime_data.result_count_label {
+                    main_vbox.add(result_count_label);
+                }
+
                 fixed.put(&main_vbox, x, y);
                 window.add(&fixed);
                 window.show_all();
 
-                // Add and show the list later, to avoid showing empty plugin categories on launch
+                // Add and show the list later, to avoid showing empty plugin categories on launch.
+                // The combined list is shown once there are matches.
+                if let Some(combined_list) = &runtime_data.combined_list {
+                    main_vbox.add(combined_list);
+                }
                 main_vbox.add(&main_list);
                 main_list.show();
+                if let Some(more_list) = &runtime_data.more_list {
+                    main_vbox.add(more_list);
+                }
+                if let Some((fallback_list, _)) = &runtime_data.fallback_list {
+                    main_vbox.add(fallback_list);
+                }
                 entry.grab_focus(); // Grab the focus so typing is immediately accepted by the entry box
+
+                if let Width::Auto { .. } = runtime_data.config.width {
+                    auto_width = Some(AutoWidth {
+                        fixed,
+                        main_vbox,
+                        center: x + width / 2,
+                        y,
+                        max_width: width,
+                    });
+                }
             }
+            runtime_data.borrow_mut().auto_width = auto_width;
+            update_width(&runtime_data.borrow());
 
-            if runtime_data.borrow().config.show_results_immediately {
+            // A plugin started in exclusive mode is shown immediately, as it is the only one
+            if runtime_data.borrow().config.show_results_immediately
+                || runtime_data.borrow().exclusive.is_some()
+            {
                 // Get initial matches
                 refresh_matches(String::new(), runtime_data);
             }
@@ -694,42 +1901,308 @@ fn activate(app: &gtk::Application, runtime_data: Rc<RefCell<RuntimeData>>) {
     window.show_all();
 }
 
-fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches: RVec<Match>) {
+/// Whether the key event is for the key with the given modifiers
+fn key_matches(event: &gdk::EventKey, key: &str, ctrl: bool, alt: bool) -> bool {
+    event
+        .keyval()
+        .name()
+        .map_or(false, |name| key.eq_ignore_ascii_case(&name))
+        && ctrl == event.state().contains(gdk::ModifierType::CONTROL_MASK)
+        && alt == event.state().contains(gdk::ModifierType::MOD1_MASK)
+}
+
+/// Handle the activation of the selected match
+fn handle_selection(
+    window: &gtk::ApplicationWindow,
+    entry: &gtk::Entry,
+    runtime_data: Rc<RefCell<RuntimeData>>,
+    term: bool,
+    keybind: Option<u64>,
+    accept: bool,
+) -> Inhibit {
+    let mut _runtime_data = runtime_data.borrow_mut();
+
+    // The view is cloned so that the runtime data can be modified while it is in use
+    let (selected_match, plugin_view) = match _runtime_data
+        .plugins
+        .iter()
+        .find_map(|view| view.selected_row().map(|row| (row, view.clone())))
+    {
+        Some(selected) => selected,
+        None => {
+            if more_selected(&_runtime_data) {
+                mem::drop(_runtime_data);
+                show_more(&runtime_data);
+                return Inhibit(true);
+            }
+            if run_fallback(&_runtime_data) {
+                window.close();
+                return Inhibit(true);
+            }
+            return Inhibit(false);
+        }
+    };
+
+    let mut _match = unsafe { (*selected_match.data::<Match>("match").unwrap().as_ptr()).clone() };
+    if term {
+        _match.term = ROption::RSome(true);
+    }
+    _match.keybind = keybind.into();
+    let result = plugin_view.plugin.handle_selection()(_match.clone());
+
+    // Store the selection to be printed if anyrun is closing
+    if _runtime_data.print_selection
+        && matches!(
+            result,
+            HandleResult::Close | HandleResult::Copy(_) | HandleResult::Stdout(_)
+        )
+    {
+        _runtime_data.selection = Some(serde_json::json!({
+            "plugin": plugin_view.plugin.info()().name.as_str(),
+            "title": _match.title.as_str(),
+            "description": _match.description.as_ref().map(|desc| desc.as_str()).into_option(),
+            "id": _match.id.into_option(),
+        }));
+    }
+
+    // Perform actions based on the result of handling the selection
+    match result {
+        // With the `Accept` action anyrun stays open for selecting more matches
+        HandleResult::Close if accept => Inhibit(true),
+        HandleResult::Stdout(bytes) if accept => {
+            _runtime_data.accepted.push(bytes.into());
+            Inhibit(true)
+        }
+        HandleResult::Close => {
+            window.close();
+            Inhibit(true)
+        }
+        HandleResult::Refresh(exclusive) => {
+            // The plugin's state has changed, so the old matches are no longer valid
+            _runtime_data
+                .match_cache
+                .borrow_mut()
+                .invalidate(&plugin_view.plugin.info()().name);
+
+            if _runtime_data.config.reselect_on_refresh {
+                _runtime_data.reselect.replace(Some((
+                    plugin_view.plugin.info()().name,
+                    _match.id,
+                    _match.title.clone(),
+                )));
+            }
+
+            let was_exclusive = _runtime_data.exclusive.is_some();
+            if exclusive {
+                _runtime_data.exclusive = Some(plugin_view);
+            } else {
+                _runtime_data.exclusive = None;
+            }
+
+            // With `exclusive_filter` the entry is cleared when entering exclusive mode so it
+            // can be used to filter the plugin's items, and restored when exiting it
+            let new_input = if !_runtime_data.config.exclusive_filter {
+                None
+            } else if exclusive && !was_exclusive {
+                _runtime_data.pre_exclusive_input = Some(entry.text().into());
+                Some(String::new())
+            } else if !exclusive && was_exclusive {
+                _runtime_data.pre_exclusive_input.take()
+            } else {
+                None
+            };
+            mem::drop(_runtime_data); // Drop the mutable borrow
+
+            match new_input {
+                // Changing the text refreshes the matches
+                Some(input) if entry.text() != input.as_str() => {
+                    entry.set_text(&input);
+                    entry.set_position(-1);
+                    entry.grab_focus_without_selecting();
+                }
+                _ => refresh_matches(entry.text().into(), runtime_data.clone()),
+            }
+            Inhibit(false)
+        }
+        HandleResult::Copy(bytes) => {
+            // Only text is formatted, other content is copied as is
+            let bytes = match (&plugin_view.copy_format, std::str::from_utf8(&bytes)) {
+                (Some(format), Ok(content)) => format
+                    .replace("{title}", &_match.title)
+                    .replace(
+                        "{description}",
+                        _match.description.as_ref().map_or("", |desc| desc.as_str()),
+                    )
+                    .replace("{content}", content)
+                    .into_bytes(),
+                _ => bytes.into(),
+            };
+            _runtime_data.post_run_action = PostRunAction::Copy(bytes);
+            window.close();
+            Inhibit(true)
+        }
+        HandleResult::Stdout(bytes) => {
+            if let Err(why) = io::stdout().lock().write_all(&bytes) {
+                eprintln!("Error outputting content to stdout: {}", why);
+            }
+            window.close();
+            Inhibit(true)
+        }
+        HandleResult::Pipe { plugin, input } => {
+            let target = _runtime_data
+                .plugins
+                .iter()
+                .find(|view| view.plugin.info()().name == plugin)
+                .cloned();
+            match target {
+                Some(target) => {
+                    _runtime_data
+                        .match_cache
+                        .borrow_mut()
+                        .invalidate(&target.plugin.info()().name);
+                    _runtime_data.exclusive = Some(target);
+                    mem::drop(_runtime_data); // Drop the mutable borrow
+
+                    // Changing the text refreshes the matches, so only refresh
+                    // manually if the text stays the same
+                    if entry.text() == input.as_str() {
+                        refresh_matches(input.into_string(), runtime_data.clone());
+                    } else {
+                        entry.set_text(&input);
+                        entry.set_position(-1);
+                    }
+                }
+                None => eprintln!("Plugin to pipe to not found: {}", plugin),
+            }
+            Inhibit(true)
+        }
+    }
+}
+
+/// The title as pango markup, with the characters at the indices in bold
+fn highlight_markup(title: &str, indices: &[u64]) -> String {
+    title
+        .chars()
+        .enumerate()
+        .map(|(index, chr)| {
+            let escaped = glib::markup_escape_text(chr.encode_utf8(&mut [0; 4]));
+            if indices.contains(&(index as u64)) {
+                format!("<b>{}</b>", escaped)
+            } else {
+                escaped.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Show or hide the description label of the match in the row, for `description_on_select`
+fn set_description_visible(widget: &gtk::Widget, visible: bool) {
+    if widget.widget_name() == style_names::MATCH_DESC {
+        widget.set_visible(visible);
+    } else if let Some(container) = widget.downcast_ref::<gtk::Container>() {
+        for child in container.children() {
+            set_description_visible(&child, visible);
+        }
+    }
+}
+
+fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, mut matches: RVec<Match>) {
+    runtime_data
+        .pending
+        .set(runtime_data.pending.get().saturating_sub(1));
+
+    if let Some(max_entries) = plugin_view.max_entries {
+        matches.truncate(max_entries);
+    }
+
     // Clear out the old matches from the list
-    for widget in plugin_view.list.children() {
-        plugin_view.list.remove(&widget);
+    for row in plugin_view.rows() {
+        plugin_view.list.remove(&row);
+    }
+
+    match (plugin_view.plugin.status())() {
+        ROption::RSome(status) => {
+            plugin_view.status.set_text(&status);
+            plugin_view.status.show();
+        }
+        ROption::RNone => plugin_view.status.hide(),
     }
 
-    // If there are no matches, hide the plugin's results
-    if matches.is_empty() {
+    // If there are no matches, hide the plugin's results. The warning about missing binaries and the
+    // status are kept visible so the user knows why the plugin isn't producing anything.
+    let notice = !plugin_view.missing.is_empty() || plugin_view.status.is_visible();
+    if matches.is_empty() && !notice {
         plugin_view.row.hide();
+        update_inline_result(runtime_data);
+        update_combined_list(runtime_data);
+        update_collapsed(runtime_data);
+        update_result_count(runtime_data);
+        update_fallback(runtime_data);
+        update_more(runtime_data);
+        update_quick_select(runtime_data);
+        update_width(runtime_data);
         return;
     }
 
-    for _match in matches {
+    for (index, _match) in matches.into_iter().enumerate() {
         let hbox = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .spacing(10)
             .name(style_names::MATCH)
             .hexpand(true)
             .build();
-        if !runtime_data.config.hide_icons {
+        // Numbered by `update_quick_select` once the order of the matches is known
+        if runtime_data.config.quick_select.is_some() {
+            hbox.add(
+                &gtk::Label::builder()
+                    .name(style_names::MATCH_INDEX)
+                    .width_chars(1)
+                    .build(),
+            );
+        }
+        // The image replaces the icon, which is still shown if the image fails to load
+        let image = _match.image.as_ref().into_option().and_then(|image| {
+            let size = plugin_view.icon_size * 2;
+            match gdk_pixbuf::Pixbuf::from_file_at_size(image.as_str(), size, size) {
+                Ok(pixbuf) => Some(pixbuf),
+                Err(why) => {
+                    eprintln!("Failed to load image file: {}", why);
+                    None
+                }
+            }
+        });
+        if let Some(pixbuf) = image {
+            hbox.add(
+                &gtk::Image::builder()
+                    .name(style_names::MATCH_IMAGE)
+                    .pixbuf(&pixbuf)
+                    .build(),
+            );
+        } else if !runtime_data.config.hide_icons {
             if let ROption::RSome(icon) = &_match.icon {
+                let icon_size = plugin_view.icon_size;
                 let mut builder = gtk::Image::builder()
                     .name(style_names::MATCH)
-                    .pixel_size(32);
+                    .pixel_size(icon_size);
 
                 let path = PathBuf::from(icon.as_str());
 
                 // If the icon path is absolute, load that file
                 if path.is_absolute() {
-                    match gdk_pixbuf::Pixbuf::from_file_at_size(icon.as_str(), 32, 32) {
+                    match gdk_pixbuf::Pixbuf::from_file_at_size(icon.as_str(), icon_size, icon_size)
+                    {
                         Ok(pixbuf) => builder = builder.pixbuf(&pixbuf),
                         Err(why) => {
-                            println!("Failed to load icon file: {}", why);
+                            eprintln!("Failed to load icon file: {}", why);
                             builder = builder.icon_name("image-missing"); // Set "broken" icon
                         }
                     }
+                } else if let Some(icon_theme) = &plugin_view.icon_theme {
+                    // Icons from a custom theme need to be loaded manually
+                    match icon_theme.load_icon(icon, icon_size, gtk::IconLookupFlags::FORCE_SIZE) {
+                        Ok(Some(pixbuf)) => builder = builder.pixbuf(&pixbuf),
+                        _ => builder = builder.icon_name(icon),
+                    }
                 } else {
                     builder = builder.icon_name(icon);
                 }
@@ -737,20 +2210,40 @@ fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches:
                 hbox.add(&builder.build());
             }
         }
+        // Titles that already use pango markup are shown as they are
+        let highlighted = match &_match.highlight {
+            ROption::RSome(indices)
+                if runtime_data.config.highlight_matches && !_match.use_pango =>
+            {
+                Some(highlight_markup(&_match.title, indices))
+            }
+            _ => None,
+        };
         let title = gtk::Label::builder()
             .name(style_names::MATCH_TITLE)
             .wrap(true)
             .xalign(0.0)
-            .use_markup(_match.use_pango)
+            .use_markup(_match.use_pango || highlighted.is_some())
             .halign(gtk::Align::Start)
             .valign(gtk::Align::Center)
             .vexpand(true)
-            .label(&_match.title)
+            .label(highlighted.as_deref().unwrap_or(&_match.title))
             .build();
 
+        // Show the raw score after the description for debugging the ranking of the matches
+        let description = match (&_match.description, _match.score) {
+            (ROption::RSome(desc), ROption::RSome(score)) if runtime_data.debug_scores => {
+                Some(format!("{} (score: {})", desc, score))
+            }
+            (ROption::RNone, ROption::RSome(score)) if runtime_data.debug_scores => {
+                Some(format!("score: {}", score))
+            }
+            (desc, _) => desc.as_ref().map(|desc| desc.to_string()).into_option(),
+        };
+
         // If a description is present, make a box with it and the title
-        match &_match.description {
-            ROption::RSome(desc) => {
+        match &description {
+            Some(desc) => {
                 let title_desc_box = gtk::Box::builder()
                     .orientation(gtk::Orientation::Vertical)
                     .name(style_names::MATCH)
@@ -761,6 +2254,7 @@ fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches:
                 title_desc_box.add(
                     &gtk::Label::builder()
                         .name(style_names::MATCH_DESC)
+                        .no_show_all(runtime_data.config.description_on_select)
                         .wrap(true)
                         .xalign(0.0)
                         .use_markup(_match.use_pango)
@@ -771,10 +2265,35 @@ fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches:
                 );
                 hbox.add(&title_desc_box);
             }
-            ROption::RNone => {
+            None => {
                 hbox.add(&title);
             }
         }
+        // Only the first label on the right side expands, so that they are next to each other
+        let mut expand = true;
+        if let ROption::RSome(hint) = &_match.hint {
+            hbox.add(
+                &gtk::Label::builder()
+                    .name(style_names::MATCH_HINT)
+                    .halign(gtk::Align::End)
+                    .valign(gtk::Align::Center)
+                    .hexpand(expand)
+                    .label(hint)
+                    .build(),
+            );
+            expand = false;
+        }
+        if let ROption::RSome(badge) = &_match.badge {
+            hbox.add(
+                &gtk::Label::builder()
+                    .name(style_names::MATCH_BADGE)
+                    .halign(gtk::Align::End)
+                    .valign(gtk::Align::Center)
+                    .hexpand(expand)
+                    .label(badge)
+                    .build(),
+            );
+        }
         let row = gtk::ListBoxRow::builder()
             .name(style_names::MATCH)
             .height_request(32)
@@ -785,25 +2304,48 @@ fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches:
         unsafe {
             row.set_data("match", _match);
         }
+        // The plugin and the position among its matches tell the matches of the plugins apart in
+        // the combined list, and order them in it
+        unsafe {
+            row.set_data("plugin", plugin_view.row.clone());
+            row.set_data("index", index);
+        }
         plugin_view.list.add(&row);
     }
 
-    // Refresh the items in the view
-    plugin_view.row.show_all();
+    // Refresh the items in the view. The combined list is shown by `update_combined_list`, the row
+    // of the plugin only shows the warning and the status then.
+    if runtime_data.combined_list.is_none() || notice {
+        plugin_view.row.show_all();
+    } else {
+        plugin_view.row.hide();
+    }
+
+    // Limit the height of the scrollable region to the first `max_visible` matches
+    if let (Some(scroll), Some(max_visible)) = (&plugin_view.scroll, plugin_view.max_visible) {
+        let height = plugin_view
+            .list
+            .children()
+            .iter()
+            .take(max_visible)
+            .map(|row| row.preferred_height().1)
+            .sum();
+        scroll.set_max_content_height(height);
+    }
 
-    let combined_matches = runtime_data
-        .plugins
-        .iter()
-        .flat_map(|view| {
-            view.list
-                .children()
-                .into_iter()
-                .map(move |child| (child.dynamic_cast::<gtk::ListBoxRow>().unwrap(), view))
-        })
-        .collect::<Vec<(gtk::ListBoxRow, &PluginView)>>();
+    let combined_matches = shown_matches(runtime_data);
 
     // If `max_entries` is set, truncate the amount of entries
     if let Some(max_matches) = runtime_data.config.max_entries {
+        // With `paginate`, each page shows another `max_entries` matches
+        let max_matches = if runtime_data.config.paginate {
+            max_matches * runtime_data.pages
+        } else {
+            max_matches
+        };
+        if combined_matches.len() > max_matches {
+            runtime_data.truncated.set(true);
+        }
         for (row, view) in combined_matches.iter().skip(max_matches) {
             view.list.remove(row);
         }
@@ -811,36 +2353,446 @@ fn handle_matches(plugin_view: PluginView, runtime_data: &RuntimeData, matches:
 
     // Hide the plugins that no longer have any entries
     for (_, view) in &combined_matches {
-        if view.list.children().is_empty() {
+        if view.rows().is_empty() && view.missing.is_empty() && !view.status.is_visible() {
             view.row.hide();
         }
     }
 
-    if let Some((row, view)) = combined_matches.get(0) {
-        view.list.select_row(Some(row));
+    // If enabled, keep the selection where the user moved it instead of jumping to the first match
+    // when slower plugins finish
+    let keep_selection = runtime_data.config.keep_selection
+        && runtime_data.navigated
+        && runtime_data
+            .plugins
+            .iter()
+            .any(|view| view.selected_row().is_some());
+
+    if !keep_selection {
+        // Select the match a plugin marked as the default selection, or the first match
+        let default_selected = combined_matches.iter().find(|(row, _)| {
+            // Rows removed due to `max_entries` no longer have a parent
+            row.parent().is_some() && {
+                // Only `Match` objects are stored as the data
+                let _match = unsafe { &*row.data::<Match>("match").unwrap().as_ptr() };
+                matches!(_match.default_selected, ROption::RSome(true))
+            }
+        });
+        // After showing another page, select its first match
+        let page_start = runtime_data
+            .config
+            .max_entries
+            .filter(|_| runtime_data.config.paginate && runtime_data.pages > 1)
+            .and_then(|max_matches| {
+                combined_matches
+                    .iter()
+                    .filter(|(row, _)| row.parent().is_some())
+                    .nth(max_matches * (runtime_data.pages - 1))
+            });
+        // After a refresh, select the match that was selected before if it is still there. Matches
+        // with an ID are found by it, as refreshing may change their title.
+        let reselect = runtime_data.reselect.borrow();
+        let reselected = reselect.as_ref().and_then(|(plugin, id, title)| {
+            combined_matches.iter().find(|(row, view)| {
+                row.parent().is_some() && view.plugin.info()().name == *plugin && {
+                    let _match = unsafe { &*row.data::<Match>("match").unwrap().as_ptr() };
+                    match id {
+                        ROption::RSome(_) => _match.id == *id,
+                        ROption::RNone => _match.title == *title,
+                    }
+                }
+            })
+        });
+        if let Some((row, view)) = reselected
+            .or(page_start)
+            .or(default_selected)
+            .or(combined_matches.get(0))
+        {
+            view.list.select_row(Some(row));
+        }
     }
+
+    // Once all plugins have returned their matches, a later refresh of them starts at the top again
+    if runtime_data.pending.get() == 0 {
+        runtime_data.reselect.take();
+    }
+
+    update_inline_result(runtime_data);
+    update_combined_list(runtime_data);
+    update_collapsed(runtime_data);
+    update_result_count(runtime_data);
+    update_fallback(runtime_data);
+    update_more(runtime_data);
+    update_quick_select(runtime_data);
+    update_width(runtime_data);
 }
 
-/// Create the info box for the plugin
-fn create_info_box(info: &PluginInfo, hide_icons: bool) -> gtk::Box {
-    let info_box = gtk::Box::builder()
-        .orientation(gtk::Orientation::Horizontal)
-        .name(style_names::PLUGIN)
-        .width_request(200)
-        .height_request(32)
-        .expand(false)
-        .spacing(10)
-        .build();
-    if !hide_icons {
-        info_box.add(
-            &gtk::Image::builder()
-                .icon_name(&info.icon)
-                .name(style_names::PLUGIN)
-                .pixel_size(32)
-                .halign(gtk::Align::Start)
-                .valign(gtk::Align::Start)
-                .build(),
-        );
+/// If a `fallback` is configured, show it once all plugins have returned no matches for the input
+fn update_fallback(runtime_data: &RuntimeData) {
+    let (fallback, (list, label)) =
+        match (&runtime_data.config.fallback, &runtime_data.fallback_list) {
+            (Some(fallback), Some(fallback_list)) => (fallback, fallback_list),
+            _ => return,
+        };
+
+    if runtime_data.pending.get() == 0
+        && !runtime_data.query.is_empty()
+        && runtime_data
+            .plugins
+            .iter()
+            .all(|view| view.rows().is_empty())
+    {
+        label.set_text(&fallback.title.replace("{}", &runtime_data.query));
+        list.show();
+        list.select_row(list.row_at_index(0).as_ref());
+    } else {
+        list.hide();
+    }
+}
+
+/// Run the command of the fallback match if it is shown, returns whether it was run
+fn run_fallback(runtime_data: &RuntimeData) -> bool {
+    let (fallback, (list, _)) = match (&runtime_data.config.fallback, &runtime_data.fallback_list) {
+        (Some(fallback), Some(fallback_list)) => (fallback, fallback_list),
+        _ => return false,
+    };
+    if !list.is_visible() {
+        return false;
+    }
+
+    if let Err(why) = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&fallback.command)
+        .arg("sh")
+        .arg(&runtime_data.query)
+        .spawn()
+    {
+        eprintln!("Failed to run the fallback command: {}", why);
+    }
+    true
+}
+
+/// The views of the plugins with distinct lists, for connecting to the signals of the lists.
+/// Plugins combined into a single list share it, so only the first of them is kept.
+fn connected_views(runtime_data: &RuntimeData) -> Vec<PluginView> {
+    let mut views = runtime_data.plugins.clone();
+    views.dedup_by(|view, previous| view.list == previous.list);
+    views
+}
+
+/// The lists of matches in the order they are shown, with whether they are visible. Plugins
+/// combined into a single list share it, so it's the only one then.
+fn match_lists(runtime_data: &RuntimeData) -> Vec<(gtk::ListBox, bool)> {
+    match &runtime_data.combined_list {
+        Some(list) => vec![(list.clone(), list.is_visible())],
+        None => runtime_data
+            .plugins
+            .iter()
+            .map(|view| (view.list.clone(), view.row.is_visible()))
+            .collect(),
+    }
+}
+
+/// All matches in the order they are shown, with the plugins they are from
+fn shown_matches(runtime_data: &RuntimeData) -> Vec<(gtk::ListBoxRow, &PluginView)> {
+    match_lists(runtime_data)
+        .into_iter()
+        .flat_map(|(list, _)| list.children())
+        .filter_map(|child| {
+            // All children of lists are GtkListBoxRow widgets
+            let row = child.dynamic_cast::<gtk::ListBoxRow>().unwrap();
+            let view = runtime_data.plugins.iter().find(|view| view.owns(&row))?;
+            Some((row, view))
+        })
+        .collect()
+}
+
+/// The matches in the order they are shown, without the ones of hidden plugins
+fn visible_matches(runtime_data: &RuntimeData) -> Vec<(gtk::ListBoxRow, gtk::ListBox)> {
+    match_lists(runtime_data)
+        .into_iter()
+        .filter(|(_, visible)| *visible)
+        .flat_map(|(list, _)| {
+            list.children().into_iter().map(move |child| {
+                (
+                    // All children of lists are GtkListBoxRow widgets
+                    child.dynamic_cast::<gtk::ListBoxRow>().unwrap(),
+                    list.clone(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// The key the matches in the combined list are sorted by, from the position of their plugin, their
+/// position among the plugin's matches and their score. With `global_sort` the highest scored
+/// matches come first and the ones without a score last, matches with the same score are ordered by
+/// the `combine_mode`.
+fn combined_key(
+    combine_mode: CombineMode,
+    global_sort: bool,
+    plugin: usize,
+    index: usize,
+    score: Option<i64>,
+) -> (Reverse<Option<i64>>, usize, usize) {
+    let score = Reverse(score.filter(|_| global_sort));
+    match combine_mode {
+        CombineMode::Block => (score, plugin, index),
+        CombineMode::RoundRobin => (score, index, plugin),
+    }
+}
+
+/// Show the combined list while it has matches, unless its only match is shown inline
+fn update_combined_list(runtime_data: &RuntimeData) {
+    let list = match &runtime_data.combined_list {
+        Some(list) => list,
+        None => return,
+    };
+
+    let inline_shown = runtime_data
+        .inline_label
+        .as_ref()
+        .map_or(false, |label| label.is_visible());
+    if list.children().is_empty() || inline_shown {
+        list.hide();
+    } else {
+        list.show_all();
+    }
+}
+
+/// If `quick_select` is enabled, number the first 9 shown matches
+fn update_quick_select(runtime_data: &RuntimeData) {
+    if runtime_data.config.quick_select.is_none() {
+        return;
+    }
+
+    for (index, (row, _)) in visible_matches(runtime_data).iter().enumerate() {
+        let label = find_named(row.upcast_ref(), style_names::MATCH_INDEX)
+            .and_then(|widget| widget.downcast::<gtk::Label>().ok());
+        if let Some(label) = label {
+            if index < 9 {
+                label.set_text(&(index + 1).to_string());
+            } else {
+                label.set_text("");
+            }
+        }
+    }
+}
+
+/// The first widget with the name in the widget and its children
+fn find_named(widget: &gtk::Widget, name: &str) -> Option<gtk::Widget> {
+    if widget.widget_name() == name {
+        Some(widget.clone())
+    } else {
+        widget
+            .downcast_ref::<gtk::Container>()?
+            .children()
+            .iter()
+            .find_map(|child| find_named(child, name))
+    }
+}
+
+/// If `paginate` is enabled, show the "Show more" row when matches were left out due to
+/// `max_entries`
+fn update_more(runtime_data: &RuntimeData) {
+    if let Some(list) = &runtime_data.more_list {
+        if runtime_data.truncated.get() {
+            list.show();
+        } else {
+            list.select_row(None::<&gtk::ListBoxRow>);
+            list.hide();
+        }
+    }
+}
+
+/// The position of the match selected next when moving up or down through the matches, as the
+/// index of its list and its index in the list. `counts` are the amounts of matches in the lists.
+/// Moves from the last match to the first one and the other way around, and selects the first or
+/// last match if nothing is selected.
+fn navigate(
+    counts: &[usize],
+    selected: Option<(usize, usize)>,
+    up: bool,
+) -> Option<(usize, usize)> {
+    let total = counts.iter().sum::<usize>();
+    if total == 0 {
+        return None;
+    }
+
+    let index = selected.map(|(list, row)| counts[..list].iter().sum::<usize>() + row);
+    let last = total - 1;
+    let mut next = match (up, index) {
+        (true, Some(0) | None) => last,
+        (true, Some(index)) => index - 1,
+        (false, Some(index)) if index < last => index + 1,
+        _ => 0,
+    };
+
+    counts.iter().enumerate().find_map(|(list, count)| {
+        if next < *count {
+            Some((list, next))
+        } else {
+            next -= count;
+            None
+        }
+    })
+}
+
+/// Whether the "Show more" row is shown and selected
+fn more_selected(runtime_data: &RuntimeData) -> bool {
+    runtime_data.more_list.as_ref().map_or(false, |list| {
+        list.is_visible() && list.selected_row().is_some()
+    })
+}
+
+/// Show another page of `max_entries` matches for the input
+fn show_more(runtime_data: &Rc<RefCell<RuntimeData>>) {
+    if let Some(list) = &runtime_data.borrow().more_list {
+        list.select_row(None::<&gtk::ListBoxRow>);
+    }
+    runtime_data.borrow_mut().pages += 1;
+    let query = runtime_data.borrow().query.clone();
+    refresh_matches(query, runtime_data.clone());
+}
+
+/// If the `width` is `Auto`, resize the main box to fit the widest match, up to its `max`
+fn update_width(runtime_data: &RuntimeData) {
+    let auto_width = match &runtime_data.auto_width {
+        Some(auto_width) => auto_width,
+        None => return,
+    };
+
+    // The natural width of the box is the width it needs to fit its contents without wrapping
+    auto_width.main_vbox.set_width_request(-1);
+    let width = auto_width
+        .main_vbox
+        .preferred_width()
+        .1
+        .min(auto_width.max_width);
+    auto_width.main_vbox.set_width_request(width);
+    auto_width.fixed.move_(
+        &auto_width.main_vbox,
+        auto_width.center - width / 2,
+        auto_width.y,
+    );
+}
+
+/// If `collapse_plugins` is enabled, hide the plugins with matches after the expanded ones. Plugins
+/// combined into a single list are not collapsed.
+fn update_collapsed(runtime_data: &RuntimeData) {
+    if !runtime_data.config.collapse_plugins || runtime_data.combined_list.is_some() {
+        return;
+    }
+
+    for view in runtime_data
+        .plugins
+        .iter()
+        .filter(|view| !view.rows().is_empty())
+        .skip(runtime_data.expanded_plugins)
+    {
+        view.row.hide();
+    }
+}
+
+/// If `collapse_plugins` is enabled, expand the plugins up to the one containing the selected match
+fn expand_to_selection(runtime_data: &mut RuntimeData) {
+    if !runtime_data.config.collapse_plugins || runtime_data.combined_list.is_some() {
+        return;
+    }
+
+    let index = runtime_data
+        .plugins
+        .iter()
+        .filter(|view| !view.rows().is_empty())
+        .position(|view| view.selected_row().is_some());
+    if let Some(index) = index {
+        runtime_data.expanded_plugins = runtime_data.expanded_plugins.max(index + 1);
+        update_inline_result(runtime_data);
+        update_collapsed(runtime_data);
+    }
+}
+
+/// If `show_result_count` is enabled, update the label with the total amount of matches
+fn update_result_count(runtime_data: &RuntimeData) {
+    let result_count_label = match &runtime_data.result_count_label {
+        Some(result_count_label) => result_count_label,
+        None => return,
+    };
+
+    let count = runtime_data
+        .plugins
+        .iter()
+        .map(|view| view.rows().len())
+        .sum::<usize>();
+
+    if count == 0 {
+        result_count_label.hide();
+    } else {
+        result_count_label.set_text(&match count {
+            1 => "1 result".to_string(),
+            count => format!("{} results", count),
+        });
+        result_count_label.show();
+    }
+}
+
+/// If `inline_first_result` is enabled and there is only a single match, show it next to the entry
+/// instead of in the list.
+fn update_inline_result(runtime_data: &RuntimeData) {
+    let inline_label = match &runtime_data.inline_label {
+        Some(inline_label) => inline_label,
+        None => return,
+    };
+
+    let rows = runtime_data
+        .plugins
+        .iter()
+        .flat_map(|view| view.rows().into_iter().map(move |row| (row, view)))
+        .collect::<Vec<_>>();
+
+    if let [(row, view)] = rows.as_slice() {
+        // Only `Match` objects are stored as the data
+        let _match = unsafe { &*row.data::<Match>("match").unwrap().as_ptr() };
+        if _match.use_pango {
+            inline_label.set_markup(&_match.title);
+        } else {
+            inline_label.set_text(&_match.title);
+        }
+        inline_label.show();
+        // The combined list is hidden by `update_combined_list`
+        if runtime_data.combined_list.is_none() {
+            view.row.hide();
+        }
+    } else {
+        inline_label.hide();
+        // Show the plugins again that were hidden due to a single match
+        for view in &runtime_data.plugins {
+            if runtime_data.combined_list.is_none() && !view.rows().is_empty() {
+                view.row.show();
+            }
+        }
+    }
+}
+
+/// Create the info box for the plugin
+fn create_info_box(info: &PluginInfo, hide_icons: bool) -> gtk::Box {
+    let info_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .name(style_names::PLUGIN)
+        .width_request(200)
+        .height_request(32)
+        .expand(false)
+        .spacing(10)
+        .build();
+    if !hide_icons {
+        info_box.add(
+            &gtk::Image::builder()
+                .icon_name(&info.icon)
+                .name(style_names::PLUGIN)
+                .pixel_size(32)
+                .halign(gtk::Align::Start)
+                .valign(gtk::Align::Start)
+                .build(),
+        );
     }
     info_box.add(
         &gtk::Label::builder()
@@ -866,26 +2818,371 @@ fn create_info_box(info: &PluginInfo, hide_icons: bool) -> gtk::Box {
     main_box
 }
 
+/// The completion of the selected match
+fn selected_completion(runtime_data: &RuntimeData) -> Option<String> {
+    runtime_data.plugins.iter().find_map(|view| {
+        view.selected_row().and_then(|row| {
+            // Only `Match` objects are stored as the data
+            let _match = unsafe { &*row.data::<Match>("match").unwrap().as_ptr() };
+            _match
+                .completion
+                .as_ref()
+                .map(|completion| completion.to_string())
+                .into_option()
+        })
+    })
+}
+
+/// Perform an `Action` triggered by a keybind
+fn handle_action(
+    action: Action,
+    window: &gtk::ApplicationWindow,
+    entry: &gtk::Entry,
+    runtime_data: Rc<RefCell<RuntimeData>>,
+) -> Inhibit {
+    match action {
+        Action::CopyAll => {
+            let mut runtime_data = runtime_data.borrow_mut();
+            let titles = shown_matches(&runtime_data)
+                .into_iter()
+                .map(|(row, _)| {
+                    // Only `Match` objects are stored as the data
+                    unsafe {
+                        (*row.data::<Match>("match").unwrap().as_ptr())
+                            .title
+                            .to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if titles.is_empty() {
+                return Inhibit(false);
+            }
+
+            runtime_data.post_run_action = PostRunAction::Copy(titles.join("\n").into_bytes());
+            window.close();
+            Inhibit(true)
+        }
+        Action::Accept => handle_selection(window, entry, runtime_data, false, None, true),
+        Action::Close => {
+            let accepted = runtime_data.borrow().accepted.join(&b'\n');
+            if !accepted.is_empty() {
+                if let Err(why) = io::stdout().lock().write_all(&accepted) {
+                    eprintln!("Error outputting content to stdout: {}", why);
+                }
+            }
+            window.close();
+            Inhibit(true)
+        }
+        Action::CycleSortMode => {
+            let plugin_view = match runtime_data
+                .borrow()
+                .plugins
+                .iter()
+                .find(|view| view.selected_row().is_some())
+            {
+                Some(view) => view.clone(),
+                None => return Inhibit(false),
+            };
+
+            let sort_modes = plugin_view.plugin.info()().sort_modes;
+            if sort_modes.len() < 2 {
+                return Inhibit(false);
+            }
+            let next = sort_modes
+                .iter()
+                .position(|sort_mode| *sort_mode == plugin_view.sort_mode.get())
+                .map_or(0, |index| (index + 1) % sort_modes.len());
+            plugin_view.sort_mode.set(sort_modes[next]);
+
+            requery_plugin(plugin_view, &runtime_data);
+            Inhibit(true)
+        }
+    }
+}
+
+/// Perform a configured `MouseAction` on a clicked match
+fn handle_mouse_action(
+    action: MouseAction,
+    row: &gtk::ListBoxRow,
+    list: &gtk::ListBox,
+    window: &gtk::ApplicationWindow,
+    entry: &gtk::Entry,
+    runtime_data: Rc<RefCell<RuntimeData>>,
+) -> Inhibit {
+    match action {
+        MouseAction::Select => {
+            list.select_row(Some(row));
+            handle_selection(window, entry, runtime_data, false, None, false)
+        }
+        MouseAction::Copy => {
+            // Only `Match` objects are stored as the data
+            let _match = unsafe { &*row.data::<Match>("match").unwrap().as_ptr() };
+            runtime_data.borrow_mut().post_run_action =
+                PostRunAction::Copy(_match.title.as_bytes().to_vec());
+            window.close();
+            Inhibit(true)
+        }
+        MouseAction::Close => {
+            window.close();
+            Inhibit(true)
+        }
+        MouseAction::Nop => Inhibit(false),
+    }
+}
+
+/// Indicate the plugin whose prefix the input starts with, using the `prefix-active` CSS class and
+/// the plugin's icon in the entry
+fn update_active_prefix(entry: &gtk::Entry, runtime_data: &RuntimeData) {
+    let input = entry.text();
+
+    // If multiple prefixes match, the longest one is the most specific
+    let active = runtime_data
+        .plugins
+        .iter()
+        .map(|view| view.plugin.info()())
+        .filter_map(|info| match &info.prefix {
+            ROption::RSome(prefix) if input.starts_with(prefix.as_str()) => {
+                Some((prefix.len(), info.icon.clone()))
+            }
+            _ => None,
+        })
+        .max_by_key(|(len, _)| *len);
+
+    match active {
+        Some((_, icon)) => {
+            entry.style_context().add_class(style_names::PREFIX_ACTIVE);
+            if !runtime_data.config.hide_icons {
+                entry.set_primary_icon_name(Some(icon.as_str()));
+            }
+        }
+        None => {
+            entry
+                .style_context()
+                .remove_class(style_names::PREFIX_ACTIVE);
+            entry.set_primary_icon_name(None);
+        }
+    }
+}
+
+/// Get the directories plugins are searched from, in order of priority
+fn plugin_paths(config_dir: &str) -> Vec<PathBuf> {
+    // Prioritise the ANYRUN_PLUGINS env var over other paths
+    let mut plugin_paths = match env::var("ANYRUN_PLUGINS") {
+        Ok(string) => string.split(':').map(PathBuf::from).collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+
+    plugin_paths.append(&mut vec![
+        format!("{}/plugins", config_dir).into(),
+        format!("{}/plugins", DEFAULT_CONFIG_DIR).into(),
+    ]);
+
+    plugin_paths
+}
+
+/// Load the plugin's dynamic library, relative paths are searched from `plugin_paths`
+fn load_plugin(plugin_path: &Path, plugin_paths: &[PathBuf]) -> PluginRef {
+    if plugin_path.is_absolute() {
+        abi_stable::library::lib_header_from_path(plugin_path)
+    } else {
+        let path = plugin_paths
+            .iter()
+            .map(|path| path.join(plugin_path))
+            .find(|path| path.exists())
+            .expect("Invalid plugin path");
+
+        abi_stable::library::lib_header_from_path(&path)
+    }
+    .and_then(|plugin| plugin.init_root_module::<PluginRef>())
+    .expect("Failed to load plugin")
+}
+
+/// Measure the time it takes to load the plugins and get the first matches from them,
+/// and print the timings as JSON
+fn bench(config: Config, config_dir: String, config_load: Duration, query: String) {
+    fn millis(duration: Duration) -> f64 {
+        duration.as_secs_f64() * 1000.0
+    }
+
+    let plugin_paths = plugin_paths(&config_dir);
+
+    let plugins = config
+        .plugins
+        .iter()
+        .map(|plugin_path| {
+            let start = Instant::now();
+            let plugin = load_plugin(plugin_path, &plugin_paths);
+            let load = start.elapsed();
+
+            let start = Instant::now();
+            plugin.init()(config_dir.clone().into());
+            // `init` runs in a thread of the plugin, the info and matches depend on its result
+            let init_state = loop {
+                match plugin.init_state()() {
+                    InitState::Running => thread::sleep(Duration::from_millis(1)),
+                    state => break state,
+                }
+            };
+            let init = start.elapsed();
+
+            if init_state == InitState::Failed {
+                return serde_json::json!({
+                    "name": plugin.info()().name.as_str(),
+                    "path": plugin_path,
+                    "status": "init_failed",
+                    "load_ms": millis(load),
+                    "init_ms": millis(init),
+                });
+            }
+
+            let start = Instant::now();
+            let matches = match plugin_input(&plugin.info()(), &query) {
+                Some(plugin_input) => {
+                    let id = plugin.get_matches()(
+                        plugin_input.into(),
+                        default_sort_mode(&plugin.info()()),
+                    );
+                    loop {
+                        match plugin.poll_matches()(id) {
+                            PollResult::Ready(matches) => break matches.len(),
+                            PollResult::Pending => thread::sleep(Duration::from_millis(1)),
+                            PollResult::Cancelled => break 0,
+                        }
+                    }
+                }
+                None => 0,
+            };
+            let first_matches = start.elapsed();
+
+            serde_json::json!({
+                "name": plugin.info()().name.as_str(),
+                "path": plugin_path,
+                "status": "ok",
+                "load_ms": millis(load),
+                "init_ms": millis(init),
+                "first_matches_ms": millis(first_matches),
+                "matches": matches,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    println!(
+        "{}",
+        serde_json::json!({
+            "config_load_ms": millis(config_load),
+            "query": query,
+            "plugins": plugins,
+        })
+    );
+}
+
+/// Check if a binary can be found in `$PATH`
+fn binary_exists(binary: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
 /// Refresh the matches from the plugins
 fn refresh_matches(input: String, runtime_data: Rc<RefCell<RuntimeData>>) {
+    runtime_data.borrow_mut().query = input.clone();
+    runtime_data.borrow_mut().queried = true;
+    let plugin_count = runtime_data.borrow().plugins.len();
+    runtime_data.borrow().pending.set(plugin_count);
+    runtime_data.borrow().truncated.set(false);
+
     for plugin_view in runtime_data.borrow().plugins.iter() {
-        let id = plugin_view.plugin.get_matches()(input.clone().into());
-        let plugin_view = plugin_view.clone();
-        let runtime_data_clone = runtime_data.clone();
-        // If a plugin has requested exclusivity, respect it
-        if let Some(exclusive) = &runtime_data.borrow().exclusive {
-            if plugin_view.plugin.info() == exclusive.plugin.info() {
-                glib::timeout_add_local(Duration::from_micros(1000), move || {
-                    async_match(plugin_view.clone(), runtime_data_clone.clone(), id)
-                });
-            } else {
-                handle_matches(plugin_view.clone(), &runtime_data.borrow(), RVec::new());
-            }
-        } else {
-            glib::timeout_add_local(Duration::from_micros(1000), move || {
-                async_match(plugin_view.clone(), runtime_data_clone.clone(), id)
-            });
+        query_plugin(plugin_view.clone(), &runtime_data, &input);
+    }
+}
+
+/// Query the plugins that requested it again with the current input
+fn refresh_requested(runtime_data: &Rc<RefCell<RuntimeData>>) {
+    let plugins = runtime_data
+        .borrow()
+        .plugins
+        .iter()
+        .filter(|plugin_view| plugin_view.plugin.refresh_requested()())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // The requests are still taken, so that they don't show matches once something is queried
+    if !runtime_data.borrow().queried {
+        return;
+    }
+
+    for plugin_view in plugins {
+        requery_plugin(plugin_view, runtime_data);
+    }
+}
+
+/// Query the plugin again with the current input, dropping its cached matches
+fn requery_plugin(plugin_view: PluginView, runtime_data: &Rc<RefCell<RuntimeData>>) {
+    runtime_data
+        .borrow()
+        .match_cache
+        .borrow_mut()
+        .invalidate(&plugin_view.plugin.info()().name);
+    let pending = runtime_data.borrow().pending.get();
+    runtime_data.borrow().pending.set(pending + 1);
+    let input = runtime_data.borrow().query.clone();
+    query_plugin(plugin_view, runtime_data, &input);
+}
+
+/// Get the matches of a single plugin for the input
+fn query_plugin(plugin_view: PluginView, runtime_data: &Rc<RefCell<RuntimeData>>, input: &str) {
+    // If a plugin has requested exclusivity, respect it
+    if let Some(exclusive) = &runtime_data.borrow().exclusive {
+        if plugin_view.plugin.info() != exclusive.plugin.info() {
+            handle_matches(plugin_view, &runtime_data.borrow(), RVec::new());
+            return;
+        }
+    }
+
+    // Plugins only get the input after their prefix, and outside of their exclusive mode are only
+    // queried if the input starts with it
+    let plugin_input = match plugin_input(&plugin_view.plugin.info()(), input) {
+        Some(plugin_input) => plugin_input,
+        None if runtime_data.borrow().exclusive.is_some() => input.to_string(),
+        None => {
+            handle_matches(plugin_view, &runtime_data.borrow(), RVec::new());
+            return;
         }
+    };
+
+    // Use the cached matches if the plugin has already been queried with the same input
+    let cached = runtime_data
+        .borrow()
+        .match_cache
+        .borrow_mut()
+        .get(&plugin_view.plugin.info()().name, input);
+    if let Some(matches) = cached {
+        handle_matches(plugin_view, &runtime_data.borrow(), matches);
+        return;
+    }
+
+    let id = plugin_view.plugin.get_matches()(plugin_input.into(), plugin_view.sort_mode.get());
+    let runtime_data = runtime_data.clone();
+    let input = input.to_string();
+    glib::timeout_add_local(Duration::from_micros(1000), move || {
+        async_match(plugin_view.clone(), runtime_data.clone(), id, &input)
+    });
+}
+
+/// The sort mode a plugin uses unless another one is configured
+fn default_sort_mode(info: &PluginInfo) -> SortMode {
+    info.sort_modes.first().copied().unwrap_or_default()
+}
+
+/// The input passed to the plugin, which is the input after the prefix of the plugin unless it
+/// wants the raw input. `None` if the plugin has a prefix the input doesn't start with.
+fn plugin_input(info: &PluginInfo, input: &str) -> Option<String> {
+    match &info.prefix {
+        ROption::RSome(prefix) if !info.raw_input => {
+            input.strip_prefix(prefix.as_str()).map(str::to_string)
+        }
+        _ => Some(input.to_string()),
     }
 }
 
@@ -894,13 +3191,315 @@ fn async_match(
     plugin_view: PluginView,
     runtime_data: Rc<RefCell<RuntimeData>>,
     id: u64,
+    input: &str,
 ) -> glib::Continue {
     match plugin_view.plugin.poll_matches()(id) {
         PollResult::Ready(matches) => {
-            handle_matches(plugin_view, &runtime_data.borrow(), matches);
+            let runtime_data = runtime_data.borrow();
+            if runtime_data.config.match_cache_size > 0 {
+                runtime_data.match_cache.borrow_mut().insert(
+                    plugin_view.plugin.info()().name.into_string(),
+                    input.to_string(),
+                    matches.clone(),
+                );
+            }
+            // Cached matches may have been shown for a newer input already, in which case these
+            // are outdated
+            if runtime_data.query == input {
+                handle_matches(plugin_view, &runtime_data, matches);
+            }
             glib::Continue(false)
         }
         PollResult::Pending => glib::Continue(true),
         PollResult::Cancelled => glib::Continue(false),
     }
 }
+
+/// Cache of the matches returned by plugins for an input, the least recently used matches are
+/// dropped first when the cache is full
+struct MatchCache {
+    size: usize,
+    /// Matches keyed by the plugin name and the input
+    entries: VecDeque<((String, String), RVec<Match>)>,
+}
+
+impl MatchCache {
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, plugin: &str, input: &str) -> Option<RVec<Match>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|((_plugin, _input), _)| _plugin == plugin && _input == input)?;
+        // Move the entry to the back as the most recently used one
+        let entry = self.entries.remove(index)?;
+        let matches = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(matches)
+    }
+
+    fn insert(&mut self, plugin: String, input: String, matches: RVec<Match>) {
+        if self.size == 0 {
+            return;
+        }
+        self.entries
+            .retain(|((_plugin, _input), _)| *_plugin != plugin || *_input != input);
+        if self.entries.len() >= self.size {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(((plugin, input), matches));
+    }
+
+    /// Drop the cached matches of a plugin, used when the plugin's state changes
+    fn invalidate(&mut self, plugin: &str) {
+        self.entries.retain(|((_plugin, _), _)| _plugin != plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn navigate_skips_empty_plugins() {
+        // The last plugin is empty
+        assert_eq!(navigate(&[2, 0], Some((0, 0)), false), Some((0, 1)));
+        assert_eq!(navigate(&[2, 0], Some((0, 1)), false), Some((0, 0)));
+        assert_eq!(navigate(&[2, 0], None, true), Some((0, 1)));
+        // An empty plugin between others
+        assert_eq!(navigate(&[1, 0, 1], Some((0, 0)), false), Some((2, 0)));
+        assert_eq!(navigate(&[1, 0, 1], Some((2, 0)), true), Some((0, 0)));
+    }
+
+    #[test]
+    fn navigate_with_a_single_plugin_with_matches() {
+        let counts = [0, 3, 0];
+        assert_eq!(navigate(&counts, None, false), Some((1, 0)));
+        assert_eq!(navigate(&counts, None, true), Some((1, 2)));
+        assert_eq!(navigate(&counts, Some((1, 2)), false), Some((1, 0)));
+        assert_eq!(navigate(&counts, Some((1, 0)), true), Some((1, 2)));
+    }
+
+    #[test]
+    fn navigate_wraps_around() {
+        let counts = [1, 2];
+        assert_eq!(navigate(&counts, Some((1, 1)), false), Some((0, 0)));
+        assert_eq!(navigate(&counts, Some((0, 0)), true), Some((1, 1)));
+        assert_eq!(navigate(&counts, Some((0, 0)), false), Some((1, 0)));
+        assert_eq!(navigate(&counts, Some((1, 0)), true), Some((0, 0)));
+    }
+
+    #[test]
+    fn combined_key_interleaves_plugins() {
+        // Two matches of the first plugin and three of the second one
+        let mut matches = vec![(1, 2), (0, 1), (1, 0), (0, 0), (1, 1)];
+        matches.sort_by_key(|(plugin, index)| {
+            combined_key(CombineMode::RoundRobin, false, *plugin, *index, None)
+        });
+        assert_eq!(matches, [(0, 0), (1, 0), (0, 1), (1, 1), (1, 2)]);
+        matches.sort_by_key(|(plugin, index)| {
+            combined_key(CombineMode::Block, false, *plugin, *index, None)
+        });
+        assert_eq!(matches, [(0, 0), (0, 1), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn combined_key_sorts_by_score() {
+        // The plugin, the position among its matches and the score of the matches
+        let mut matches = vec![
+            (0, 0, Some(10)),
+            (0, 1, Some(5)),
+            (0, 2, None),
+            (1, 0, None),
+            (1, 1, None),
+            (2, 0, Some(20)),
+            (2, 1, Some(5)),
+        ];
+        let key = |combine_mode, global_sort| {
+            move |(plugin, index, score): &(usize, usize, Option<i64>)| {
+                combined_key(combine_mode, global_sort, *plugin, *index, *score)
+            }
+        };
+        // Matches without a score come last, the same scores keep the order of the combine mode
+        matches.sort_by_key(key(CombineMode::Block, true));
+        assert_eq!(
+            matches,
+            [
+                (2, 0, Some(20)),
+                (0, 0, Some(10)),
+                (0, 1, Some(5)),
+                (2, 1, Some(5)),
+                (0, 2, None),
+                (1, 0, None),
+                (1, 1, None),
+            ]
+        );
+        matches.sort_by_key(key(CombineMode::RoundRobin, true));
+        assert_eq!(
+            matches,
+            [
+                (2, 0, Some(20)),
+                (0, 0, Some(10)),
+                (0, 1, Some(5)),
+                (2, 1, Some(5)),
+                (1, 0, None),
+                (1, 1, None),
+                (0, 2, None),
+            ]
+        );
+        // The scores are ignored without `global_sort`
+        matches.sort_by_key(key(CombineMode::RoundRobin, false));
+        assert_eq!(
+            matches,
+            [
+                (0, 0, Some(10)),
+                (1, 0, None),
+                (2, 0, Some(20)),
+                (0, 1, Some(5)),
+                (1, 1, None),
+                (2, 1, Some(5)),
+                (0, 2, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn navigate_without_matches() {
+        assert_eq!(navigate(&[], None, false), None);
+        assert_eq!(navigate(&[0, 0], None, true), None);
+    }
+
+    fn plugin_info(prefix: Option<&str>, raw_input: bool) -> PluginInfo {
+        PluginInfo {
+            name: "Test".into(),
+            icon: "help-about".into(),
+            requires: RVec::new(),
+            keybinds: RVec::new(),
+            prefix: prefix.map(RString::from).into(),
+            raw_input,
+            sort_modes: RVec::new(),
+        }
+    }
+
+    #[test]
+    fn plugin_input_strips_multibyte_prefixes() {
+        let info = plugin_info(Some("🔍"), false);
+        assert_eq!(plugin_input(&info, "🔍query").as_deref(), Some("query"));
+        assert_eq!(plugin_input(&info, "🔍").as_deref(), Some(""));
+        assert_eq!(plugin_input(&info, "🔍🔍").as_deref(), Some("🔍"));
+        assert_eq!(plugin_input(&info, "query🔍"), None);
+        assert_eq!(plugin_input(&info, ""), None);
+
+        let info = plugin_info(Some(":ü"), false);
+        assert_eq!(plugin_input(&info, ":üquery").as_deref(), Some("query"));
+        assert_eq!(plugin_input(&info, ":ü").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn plugin_input_with_partial_prefixes() {
+        // The input is only a part of the prefix
+        let info = plugin_info(Some("👨‍👩‍👧"), false);
+        assert_eq!(plugin_input(&info, "👨"), None);
+        assert_eq!(plugin_input(&info, "👨‍👩"), None);
+        assert_eq!(plugin_input(&info, "👩‍👧"), None);
+        assert_eq!(plugin_input(&info, "👨‍👩‍👧 a").as_deref(), Some(" a"));
+
+        // The input starts with the same bytes as the prefix, but a different character
+        let info = plugin_info(Some("é"), false);
+        assert_eq!(plugin_input(&info, "è"), None);
+        assert_eq!(plugin_input(&info, "e\u{301}"), None);
+    }
+
+    #[test]
+    fn plugin_input_without_stripping() {
+        assert_eq!(
+            plugin_input(&plugin_info(None, false), "🔍query").as_deref(),
+            Some("🔍query")
+        );
+        assert_eq!(
+            plugin_input(&plugin_info(Some("🔍"), true), "query").as_deref(),
+            Some("query")
+        );
+    }
+
+    /// Every name and class applied to the widgets is listed by `anyrun style-names`, and nothing
+    /// else is. The widgets are built in this file, so which constant is used where is read from it.
+    #[test]
+    fn style_names_are_listed() {
+        let source = include_str!("main.rs");
+        let module = source
+            .split_once("mod style_names {")
+            .and_then(|(_, module)| module.split_once("\n}\n"))
+            .unwrap()
+            .0;
+        let consts = module
+            .lines()
+            .filter_map(|line| {
+                line.trim()
+                    .strip_prefix("pub const ")?
+                    .strip_suffix("\";")?
+                    .split_once(": &str = \"")
+            })
+            .collect::<Vec<_>>();
+        assert!(!consts.is_empty());
+
+        let mut names = BTreeSet::new();
+        let mut classes = BTreeSet::new();
+        for (ident, value) in consts {
+            let path = format!("style_names::{}", ident);
+            for (index, _) in source.match_indices(&path) {
+                let after = source[index + path.len()..].chars().next().unwrap();
+                if after.is_alphanumeric() || after == '_' {
+                    continue;
+                }
+                if source[..index].ends_with("_class(") {
+                    classes.insert(value);
+                } else {
+                    names.insert(value);
+                }
+            }
+        }
+
+        let listed = |list: &[(&'static str, &str, &str)]| {
+            list.iter()
+                .map(|(name, _, _)| *name)
+                .collect::<BTreeSet<_>>()
+        };
+        assert_eq!(listed(style_names::NAMES), names);
+        assert_eq!(listed(style_names::CLASSES), classes);
+        assert_eq!(style_names::NAMES.len(), names.len());
+        assert_eq!(style_names::CLASSES.len(), classes.len());
+    }
+
+    #[test]
+    fn stdin_config_leaves_the_rest() {
+        let mut input: &[u8] = b"Config(\n  x: 1,\n)\n---\nline1\nline2\n";
+        assert_eq!(
+            read_stdin_config(&mut input).unwrap(),
+            "Config(\n  x: 1,\n)\n"
+        );
+        assert_eq!(input, b"line1\nline2\n");
+
+        let mut input: &[u8] = b"Config()\0line1";
+        assert_eq!(read_stdin_config(&mut input).unwrap(), "Config()");
+        assert_eq!(input, b"line1");
+    }
+
+    #[test]
+    fn stdin_config_without_delimiter() {
+        let mut input: &[u8] = b"Config(\n  text: \"---\",\n)";
+        assert_eq!(
+            read_stdin_config(&mut input).unwrap(),
+            "Config(\n  text: \"---\",\n)"
+        );
+        let mut input: &[u8] = b"Config()\n---";
+        assert_eq!(read_stdin_config(&mut input).unwrap(), "Config()\n");
+    }
+}
@@ -80,6 +80,10 @@
           symbols = mkPlugin "symbols";
           translate = mkPlugin "translate";
           websearch = mkPlugin "websearch";
+          dbus-actions = mkPlugin "dbus-actions";
+          scripts = mkPlugin "scripts";
+          files = mkPlugin "files";
+          quickmath = mkPlugin "quickmath";
         };
 
         # Set up an overlay from packages exposed by this flake
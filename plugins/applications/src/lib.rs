@@ -3,36 +3,111 @@ use anyrun_plugin::{anyrun_interface::HandleResult, *};
 use fuzzy_matcher::FuzzyMatcher;
 use scrubber::DesktopEntry;
 use serde::Deserialize;
-use std::{env, fs, process::Command};
+use std::{cmp::Reverse, env, process::Command};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
 pub struct Config {
     desktop_actions: bool,
+    #[config(default = 5)]
     max_entries: usize,
     terminal: Option<String>,
+    min_score: i64,
+    #[config(default = true)]
+    match_description: bool,
+    /// Run the entries in a login shell of the user's `$SHELL`, so that the environment from the
+    /// user's profile is available
+    login_shell: bool,
+    /// Rank the applications that were selected before for a similar query higher
+    remember_selections: bool,
+    /// Match against the names lowercased and with punctuation removed, the names are still shown
+    /// as they are
+    normalize: bool,
+    /// Cache the desktop entries, so that they don't have to be loaded from the desktop files on
+    /// every launch
+    cache_entries: bool,
+    /// With `desktop_actions`, show the actions in a submenu opened by selecting their application
+    /// instead of in the main list
+    actions_submenu: bool,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            desktop_actions: false,
-            max_entries: 5,
-            terminal: None,
+pub struct State {
+    config: Config,
+    entries: Vec<(DesktopEntry, u64)>,
+    history: Option<SelectionHistory>,
+    /// The ID of the application whose actions are shown in the submenu
+    selection: Option<u64>,
+}
+
+/// The ID of the match leaving the actions submenu
+const BACK_ID: u64 = u64::MAX;
+
+/// The score added for each time an entry was selected for a similar query
+const SELECTION_BONUS: i64 = 250;
+
+/// The score added per character when the input matches the initials of the name, like "vsc" for
+/// "Visual Studio Code"
+const ACRONYM_BONUS: i64 = 500;
+
+/// The first letters of the words in the name, including the capitalized parts of words like
+/// "LibreOffice", lowercased
+fn initials(name: &str) -> String {
+    let mut initials = String::new();
+    let mut previous: Option<char> = None;
+    for chr in name.chars() {
+        let word_start = match previous {
+            Some(previous) => {
+                !previous.is_alphanumeric() || (previous.is_lowercase() && chr.is_uppercase())
+            }
+            None => true,
+        };
+        if chr.is_alphanumeric() && word_start {
+            initials.extend(chr.to_lowercase());
         }
+        previous = Some(chr);
     }
+    initials
 }
 
-pub struct State {
-    config: Config,
-    entries: Vec<(DesktopEntry, u64)>,
+/// Lowercase the text and replace punctuation with spaces, collapsing repeated whitespace
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
 }
 
-mod scrubber;
+/// The desktop actions of the application, which carry its name as the description and its icon
+fn actions<'a>(
+    state: &'a State,
+    app: &'a DesktopEntry,
+) -> impl Iterator<Item = &'a (DesktopEntry, u64)> {
+    state
+        .entries
+        .iter()
+        .filter(move |(entry, _)| entry.desc.as_ref() == Some(&app.name) && entry.icon == app.icon)
+}
+
+/// Identifies the entry in the selection history
+fn history_key(entry: &DesktopEntry) -> String {
+    match &entry.desc {
+        Some(desc) => format!("{}: {}", desc, entry.name),
+        None => entry.name.clone(),
+    }
+}
 
-const SENSIBLE_TERMINALS: &[&str] = &["alacritty", "foot", "kitty", "wezterm", "wterm"];
+mod scrubber;
 
 #[handler]
-pub fn handler(selection: Match, state: &State) -> HandleResult {
+pub fn handler(selection: Match, state: &mut State) -> HandleResult {
+    if selection.id == ROption::RSome(BACK_ID) {
+        state.selection = None;
+        return HandleResult::Refresh(false);
+    }
+
     let entry = state
         .entries
         .iter()
@@ -45,31 +120,48 @@ pub fn handler(selection: Match, state: &State) -> HandleResult {
         })
         .unwrap();
 
-    if entry.term {
-        match &state.config.terminal {
-            Some(term) => {
-                if let Err(why) = Command::new(term).arg("-e").arg(&entry.exec).spawn() {
-                    eprintln!("Error running desktop entry: {}", why);
-                }
-            }
-            None => {
-                for term in SENSIBLE_TERMINALS {
-                    if Command::new(term)
-                        .arg("-e")
-                        .arg(&entry.exec)
-                        .spawn()
-                        .is_ok()
-                    {
-                        break;
-                    }
-                }
-            }
+    // Open the submenu for applications with actions instead of launching them
+    if state.config.desktop_actions
+        && state.config.actions_submenu
+        && state.selection.is_none()
+        && entry.desc.is_none()
+        && actions(state, entry).next().is_some()
+    {
+        state.selection = Some(selection.id.unwrap());
+        return HandleResult::Refresh(true);
+    }
+
+    if let Some(history) = &state.history {
+        history.record(&history_key(entry));
+    }
+
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    // The command to run in the terminal, the terminal runs the `Exec` itself by default
+    let term_command = if state.config.login_shell {
+        vec![shell.as_str(), "-lc", entry.exec.as_str()]
+    } else {
+        vec![entry.exec.as_str()]
+    };
+
+    // Run in a terminal if the entry needs it or it was activated with Ctrl+Enter
+    if entry.term || matches!(selection.term, ROption::RSome(true)) {
+        if let Err(why) = spawn_in_terminal(state.config.terminal.as_deref(), &term_command) {
+            eprintln!("Error running desktop entry: {}", why);
         }
     } else if let Err(why) = {
         let current_dir = &env::current_dir().unwrap();
 
-        Command::new("sh")
-            .arg("-c")
+        let mut command = if state.config.login_shell {
+            let mut command = Command::new(&shell);
+            command.arg("-lc");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+
+        command
             .arg(&entry.exec)
             .current_dir(if let Some(path) = &entry.path {
                 if path.exists() { path } else { current_dir }
@@ -87,53 +179,155 @@ pub fn handler(selection: Match, state: &State) -> HandleResult {
 
 #[init]
 pub fn init(config_dir: RString) -> State {
-    let config: Config = match fs::read_to_string(format!("{}/applications.ron", config_dir)) {
-        Ok(content) => ron::from_str(&content).unwrap_or_else(|why| {
-            eprintln!("Error parsing applications plugin config: {}", why);
-            Config::default()
-        }),
-        Err(why) => {
-            eprintln!("Error reading applications plugin config: {}", why);
-            Config::default()
+    let config: Config = load_config(&config_dir, "applications.ron");
+
+    // The entries differ depending on whether the desktop actions are included
+    let cache_name = if config.desktop_actions {
+        "applications-actions"
+    } else {
+        "applications"
+    };
+    let sources = scrubber::source_dirs();
+
+    let cached = config
+        .cache_entries
+        .then(|| load_cache(cache_name, &sources))
+        .flatten();
+
+    let entries = match cached {
+        Some(entries) => entries,
+        None => {
+            let entries = scrubber::scrubber(&config).unwrap_or_else(|why| {
+                eprintln!("Failed to load desktop entries: {}", why);
+                Vec::new()
+            });
+            if config.cache_entries {
+                store_cache(cache_name, &sources, &entries);
+            }
+            entries
         }
     };
 
-    let entries = scrubber::scrubber(&config).unwrap_or_else(|why| {
-        eprintln!("Failed to load desktop entries: {}", why);
-        Vec::new()
-    });
+    let history = config
+        .remember_selections
+        .then(|| SelectionHistory::load("applications", 500));
+
+    State {
+        config,
+        entries,
+        history,
+        selection: None,
+    }
+}
+
+/// The matches of the actions submenu: the application itself, its actions and a way back
+fn submenu_matches(state: &State, app_id: u64) -> RVec<Match> {
+    let app = match state.entries.iter().find(|(_, id)| *id == app_id) {
+        Some(app) => app,
+        None => return RVec::new(),
+    };
+
+    let entry_match = |(entry, id): &(DesktopEntry, u64)| Match {
+        title: entry.name.clone().into(),
+        description: entry.desc.clone().map(|desc| desc.into()).into(),
+        use_pango: false,
+        icon: ROption::RSome(entry.icon.clone().into()),
+        id: ROption::RSome(*id),
+        highlight: ROption::RNone,
+        image: ROption::RNone,
+        keybind: ROption::RNone,
+        term: ROption::RSome(entry.term),
+        default_selected: ROption::RNone,
+        completion: ROption::RNone,
+        score: ROption::RNone,
+        hint: ROption::RNone,
+        badge: ROption::RNone,
+    };
+
+    let mut app_actions = actions(state, &app.0).collect::<Vec<_>>();
+    // The offset is the position of the action in the desktop file
+    app_actions.sort_by_key(|(entry, _)| entry.offset);
 
-    State { config, entries }
+    std::iter::once(entry_match(app))
+        .chain(app_actions.into_iter().map(entry_match))
+        .chain(std::iter::once(Match {
+            title: "Back".into(),
+            description: ROption::RNone,
+            use_pango: false,
+            icon: ROption::RSome("edit-undo".into()),
+            id: ROption::RSome(BACK_ID),
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RNone,
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        }))
+        .collect()
 }
 
 #[get_matches]
 pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
+    if let Some(app_id) = state.selection {
+        return submenu_matches(state, app_id);
+    }
+
+    let submenu = state.config.desktop_actions && state.config.actions_submenu;
     let matcher = fuzzy_matcher::skim::SkimMatcherV2::default().smart_case();
+    if let Some(history) = &state.history {
+        history.set_query(&input);
+    }
+    // The text that is matched against, the displayed names are left untouched
+    let text = |text: &str| {
+        if state.config.normalize {
+            normalize(text)
+        } else {
+            text.to_string()
+        }
+    };
+    let input = text(&input);
+    let acronym = input.to_lowercase();
     let mut entries = state
         .entries
         .iter()
+        // In the submenu mode the actions are only shown in the submenu
+        .filter(|(entry, _)| !(submenu && entry.desc.is_some()))
         .filter_map(|(entry, id)| {
             let app_score = match &entry.desc {
-                None => matcher.fuzzy_match(&entry.name, &input).unwrap_or(0),
-                Some(val) => matcher
-                    .fuzzy_match(&format!("{} {}", &val, &entry.name).to_string(), &input)
+                Some(val) if state.config.match_description => matcher
+                    .fuzzy_match(&text(&format!("{} {}", &val, &entry.name)), &input)
                     .unwrap_or(0),
+                _ => matcher.fuzzy_match(&text(&entry.name), &input).unwrap_or(0),
             };
 
             let keyword_score = entry
                 .keywords
                 .iter()
-                .map(|keyword| matcher.fuzzy_match(keyword, &input).unwrap_or(0))
+                .map(|keyword| matcher.fuzzy_match(&text(keyword), &input).unwrap_or(0))
                 .sum::<i64>();
 
             let mut score = (app_score * 25 + keyword_score) - entry.offset;
 
+            // The skim matcher doesn't favor matching only the first letters of the words
+            if acronym.chars().count() >= 2 && initials(&entry.name).starts_with(&acronym) {
+                score += acronym.chars().count() as i64 * ACRONYM_BONUS;
+            }
+
             // prioritize actions
             if entry.desc.is_some() {
                 score = score * 2;
             }
 
-            if score > 0 {
+            if let Some(history) = &state.history {
+                if score > 0 {
+                    score += history.count(&history_key(entry)) as i64 * SELECTION_BONUS;
+                }
+            }
+
+            if score > 0 && meets_min_score(&input, score, state.config.min_score) {
                 Some((entry, *id, score))
             } else {
                 None
@@ -143,15 +337,50 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
 
     entries.sort_by(|a, b| b.2.cmp(&a.2));
 
+    // The sorts are stable, so the entries that are equal in the sort mode stay sorted by relevance
+    match sort_mode() {
+        SortMode::Name => entries.sort_by_cached_key(|(entry, _, _)| entry.name.to_lowercase()),
+        SortMode::Frequency => {
+            if let Some(history) = &state.history {
+                entries.sort_by_cached_key(|(entry, _, _)| {
+                    Reverse(history.total(&history_key(entry)))
+                });
+            }
+        }
+        _ => (),
+    }
+
     entries.truncate(state.config.max_entries);
     entries
         .into_iter()
-        .map(|(entry, id, _)| Match {
+        .map(|(entry, id, score)| Match {
             title: entry.name.clone().into(),
             description: entry.desc.clone().map(|desc| desc.into()).into(),
             use_pango: false,
             icon: ROption::RSome(entry.icon.clone().into()),
             id: ROption::RSome(id),
+            // The indices wouldn't match the shown name if the name was normalized
+            highlight: if state.config.normalize {
+                ROption::RNone
+            } else {
+                matcher
+                    .fuzzy_indices(&entry.name, &input)
+                    .map(|(_, indices)| {
+                        indices
+                            .into_iter()
+                            .map(|index| index as u64)
+                            .collect::<RVec<_>>()
+                    })
+                    .into()
+            },
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RSome(entry.term),
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RSome(score),
+            hint: ROption::RNone,
+            badge: ROption::RNone,
         })
         .collect()
 }
@@ -161,5 +390,190 @@ pub fn info() -> PluginInfo {
     PluginInfo {
         name: "Applications".into(),
         icon: "application-x-executable".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::from(vec![
+            SortMode::Relevance,
+            SortMode::Name,
+            SortMode::Frequency,
+        ]),
+        prefix: ROption::RNone,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyrun_plugin_testing::*;
+
+    fn entry(name: &str, desc: Option<&str>) -> DesktopEntry {
+        DesktopEntry {
+            exec: String::new(),
+            path: None,
+            name: name.to_string(),
+            keywords: Vec::new(),
+            desc: desc.map(str::to_string),
+            icon: "application-x-executable".to_string(),
+            term: false,
+            offset: 0,
+        }
+    }
+
+    /// A state with the entries instead of the ones on the system, configured like `init` would
+    fn state(config: &str, entries: Vec<DesktopEntry>) -> State {
+        let config_dir = ConfigDir::new().with_file("applications.ron", config);
+        State {
+            config: load_config(&config_dir.path(), "applications.ron"),
+            entries: entries.into_iter().zip(0..).collect(),
+            history: None,
+            selection: None,
+        }
+    }
+
+    #[test]
+    fn actions_submenu() {
+        let mut state = state(
+            "Config(desktop_actions: true, actions_submenu: true)",
+            vec![
+                entry("Firefox", None),
+                entry("New Private Window", Some("Firefox")),
+                entry("New Window", Some("Firefox")),
+            ],
+        );
+
+        let matches = get_matches("fire".into(), &state);
+        assert_eq!(titles(&matches), ["Firefox"]);
+
+        let firefox = find_match(&matches, "Firefox").unwrap().clone();
+        assert!(matches!(
+            handler(firefox, &mut state),
+            HandleResult::Refresh(true)
+        ));
+
+        let matches = get_matches("".into(), &state);
+        assert_eq!(
+            titles(&matches),
+            ["Firefox", "New Private Window", "New Window", "Back"]
+        );
+
+        let back = find_match(&matches, "Back").unwrap().clone();
+        assert!(matches!(
+            handler(back, &mut state),
+            HandleResult::Refresh(false)
+        ));
+        assert_eq!(titles(&get_matches("fire".into(), &state)), ["Firefox"]);
+    }
+
+    #[test]
+    fn min_score_drops_weak_matches() {
+        let entries = || {
+            vec![
+                entry("Text Editor", None),
+                entry("Thunderbird", None),
+                entry("Firefox", None),
+            ]
+        };
+
+        let matches = get_matches("te".into(), &state("Config()", entries()));
+        assert_eq!(titles(&matches), ["Text Editor", "Thunderbird"]);
+
+        let matches = get_matches("te".into(), &state("Config(min_score: 1000)", entries()));
+        assert_eq!(titles(&matches), ["Text Editor"]);
+    }
+
+    #[test]
+    fn match_description() {
+        let entries = || vec![entry("Files", None), entry("New Window", Some("Firefox"))];
+
+        // Only the description of the action matches
+        let matches = get_matches(
+            "firefox".into(),
+            &state("Config(desktop_actions: true)", entries()),
+        );
+        assert_eq!(titles(&matches), ["New Window"]);
+
+        let matches = get_matches(
+            "firefox".into(),
+            &state(
+                "Config(desktop_actions: true, match_description: false)",
+                entries(),
+            ),
+        );
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn normalize_punctuation() {
+        assert_eq!(
+            normalize("Visual Studio Code - URL Handler"),
+            "visual studio code url handler"
+        );
+        assert_eq!(normalize("  Qt-Designer (v6.5)!  "), "qt designer v6 5");
+        assert_eq!(
+            normalize("GNU Image Manipulation Program"),
+            "gnu image manipulation program"
+        );
+    }
+
+    #[test]
+    fn normalized_matching_keeps_the_names() {
+        let entries = || {
+            vec![
+                entry("Qt Designer", None),
+                entry("Visual Studio Code", None),
+            ]
+        };
+
+        let matches = get_matches("qt-designer".into(), &state("Config()", entries()));
+        assert!(matches.is_empty());
+        let matches = get_matches("VISUAL".into(), &state("Config()", entries()));
+        assert!(matches.is_empty());
+
+        let matches = get_matches(
+            "qt-designer".into(),
+            &state("Config(normalize: true)", entries()),
+        );
+        assert_eq!(titles(&matches), ["Qt Designer"]);
+        let matches = get_matches(
+            "VISUAL".into(),
+            &state("Config(normalize: true)", entries()),
+        );
+        assert_eq!(titles(&matches), ["Visual Studio Code"]);
+        // The highlighted indices would be of the normalized name
+        assert!(matches!(matches[0].highlight, ROption::RNone));
+    }
+
+    #[test]
+    fn initials_of_names() {
+        assert_eq!(initials("Visual Studio Code"), "vsc");
+        assert_eq!(initials("GNU Image Manipulation Program"), "gimp");
+        assert_eq!(initials("LibreOffice Writer"), "low");
+        assert_eq!(initials("Visual Studio Code - URL Handler"), "vscuh");
+        assert_eq!(initials("Firefox"), "f");
+        assert_eq!(initials(""), "");
+    }
+
+    #[test]
+    fn acronyms_outrank_plain_fuzzy_matches() {
+        // Without the acronym bonus, "Glimpse" and "Vscode Settings" would be ranked first
+        let state = state(
+            "Config()",
+            vec![
+                entry("Glimpse", None),
+                entry("GNU Image Manipulation Program", None),
+                entry("Vscode Settings", None),
+                entry("Visual Studio Code", None),
+            ],
+        );
+
+        let matches = get_matches("gimp".into(), &state);
+        assert_eq!(
+            titles(&matches),
+            ["GNU Image Manipulation Program", "Glimpse"]
+        );
+
+        let matches = get_matches("vsc".into(), &state);
+        assert_eq!(titles(&matches), ["Visual Studio Code", "Vscode Settings"]);
     }
 }
@@ -1,8 +1,10 @@
 use std::{collections::HashMap, env, ffi::OsStr, fs, path::PathBuf};
 
+use serde::{Deserialize, Serialize};
+
 use crate::Config;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DesktopEntry {
     pub exec: String,
     pub path: Option<PathBuf>,
@@ -166,6 +168,28 @@ impl DesktopEntry {
     }
 }
 
+/// The directories the desktop entries are loaded from, used to tell whether the cached entries
+/// are outdated
+pub fn source_dirs() -> Vec<PathBuf> {
+    let mut dirs = match env::var("XDG_DATA_DIRS") {
+        Ok(data_dirs) => data_dirs
+            .split(':')
+            .map(|dir| PathBuf::from(format!("{}/applications/", dir)))
+            .collect(),
+        Err(_) => vec![PathBuf::from("/usr/share/applications/")],
+    };
+
+    dirs.push(PathBuf::from(match env::var("XDG_DATA_HOME") {
+        Ok(data_home) => format!("{}/applications/", data_home),
+        Err(_) => format!(
+            "{}/.local/share/applications/",
+            env::var("HOME").unwrap_or_default()
+        ),
+    }));
+
+    dirs
+}
+
 pub fn scrubber(config: &Config) -> Result<Vec<(DesktopEntry, u64)>, Box<dyn std::error::Error>> {
     // Create iterator over all the files in the XDG_DATA_DIRS
     // XDG compliancy is cool
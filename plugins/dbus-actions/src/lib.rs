@@ -0,0 +1,224 @@
+use abi_stable::std_types::{ROption, RString, RVec};
+use anyrun_plugin::*;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Deserialize;
+use zbus::{
+    blocking::Connection,
+    zvariant::{StructureBuilder, Value},
+};
+
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
+pub struct Config {
+    prefix: String,
+    #[config(default = 5)]
+    max_entries: usize,
+    min_score: i64,
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize, Default)]
+enum Bus {
+    #[default]
+    Session,
+    System,
+}
+
+/// An argument of the method call, the variant determines the DBus type
+#[derive(Deserialize)]
+enum Arg {
+    Bool(bool),
+    Byte(u8),
+    Int32(i32),
+    UInt32(u32),
+    Int64(i64),
+    UInt64(u64),
+    Double(f64),
+    String(String),
+    /// A variant containing the argument, like the value of `org.freedesktop.DBus.Properties.Set`
+    Variant(Box<Arg>),
+}
+
+impl Arg {
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            Self::Bool(val) => Value::from(*val),
+            Self::Byte(val) => Value::from(*val),
+            Self::Int32(val) => Value::from(*val),
+            Self::UInt32(val) => Value::from(*val),
+            Self::Int64(val) => Value::from(*val),
+            Self::UInt64(val) => Value::from(*val),
+            Self::Double(val) => Value::from(*val),
+            Self::String(val) => Value::from(val.as_str()),
+            Self::Variant(val) => Value::Value(Box::new(val.to_value())),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Action {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    bus: Bus,
+    service: String,
+    path: String,
+    interface: String,
+    method: String,
+    #[serde(default)]
+    args: Vec<Arg>,
+}
+
+impl Action {
+    fn call(&self) -> zbus::Result<()> {
+        let connection = match self.bus {
+            Bus::Session => Connection::session()?,
+            Bus::System => Connection::system()?,
+        };
+
+        // An empty structure would still get a signature, so methods without arguments
+        // are called with an empty body instead
+        if self.args.is_empty() {
+            connection.call_method(
+                Some(self.service.as_str()),
+                self.path.as_str(),
+                Some(self.interface.as_str()),
+                self.method.as_str(),
+                &(),
+            )?;
+        } else {
+            let body = self
+                .args
+                .iter()
+                .fold(StructureBuilder::new(), |builder, arg| {
+                    builder.append_field(arg.to_value())
+                })
+                .build();
+
+            connection.call_method(
+                Some(self.service.as_str()),
+                self.path.as_str(),
+                Some(self.interface.as_str()),
+                self.method.as_str(),
+                &body,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct State {
+    config: Config,
+    /// The error of the last failed call, shown until it is dismissed
+    error: Option<String>,
+}
+
+#[init]
+pub fn init(config_dir: RString) -> State {
+    State {
+        config: load_config(&config_dir, "dbus-actions.ron"),
+        error: None,
+    }
+}
+
+#[info]
+pub fn info(state: Option<&State>) -> PluginInfo {
+    PluginInfo {
+        name: "DBus actions".into(),
+        icon: "preferences-system".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: state
+            .map(|state| state.config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
+    }
+}
+
+#[get_matches]
+pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
+    if let Some(error) = &state.error {
+        return vec![Match {
+            title: "Failed to call the DBus method".into(),
+            description: ROption::RSome(error.clone().into()),
+            use_pango: false,
+            icon: ROption::RSome("dialog-error".into()),
+            id: ROption::RNone,
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RNone,
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        }]
+        .into();
+    }
+
+    // anyrun has already removed the prefix
+    let input = input.trim();
+
+    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default().smart_case();
+    let mut actions = state
+        .config
+        .actions
+        .iter()
+        .enumerate()
+        .filter_map(|(id, action)| {
+            matcher
+                .fuzzy_match(&action.name, input)
+                .filter(|score| meets_min_score(input, *score, state.config.min_score))
+                .map(|score| (action, id, score))
+        })
+        .collect::<Vec<_>>();
+
+    actions.sort_by(|a, b| b.2.cmp(&a.2));
+
+    actions.truncate(state.config.max_entries);
+    actions
+        .into_iter()
+        .map(|(action, id, score)| Match {
+            title: action.name.clone().into(),
+            description: action.description.clone().map(|desc| desc.into()).into(),
+            use_pango: false,
+            icon: action.icon.clone().map(|icon| icon.into()).into(),
+            id: ROption::RSome(id as u64),
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RSome(score),
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        })
+        .collect()
+}
+
+#[handler]
+pub fn handler(selection: Match, state: &mut State) -> HandleResult {
+    // Selecting the error dismisses it
+    if state.error.take().is_some() {
+        return HandleResult::Refresh(false);
+    }
+
+    let action = &state.config.actions[selection.id.unwrap() as usize];
+
+    match action.call() {
+        Ok(()) => HandleResult::Close,
+        Err(why) => {
+            state.error = Some(format!("{}: {}", action.name, why));
+            HandleResult::Refresh(true)
+        }
+    }
+}
@@ -1,24 +1,16 @@
-use std::fs;
-
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
 use serde::Deserialize;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
 pub struct Config {
+    #[config(default = ":def".to_string())]
     prefix: String,
+    #[config(default = 3, min = 1)]
     max_entries: usize,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            prefix: ":def".to_string(),
-            max_entries: 3,
-        }
-    }
-}
-
 #[derive(Deserialize)]
 struct ApiResponse {
     meanings: Vec<Meaning>,
@@ -38,10 +30,7 @@ struct Definition {
 
 #[init]
 pub fn init(config_dir: RString) -> Config {
-    match fs::read_to_string(format!("{}/dictionary.ron", config_dir)) {
-        Ok(content) => ron::from_str(&content).unwrap_or_default(),
-        Err(_) => Config::default(),
-    }
+    load_config(&config_dir, "dictionary.ron")
 }
 
 #[handler]
@@ -51,25 +40,28 @@ pub fn handler(_match: Match) -> HandleResult {
 
 #[get_matches]
 pub fn get_matches(input: RString, config: &Config) -> RVec<Match> {
-    let input = if let Some(input) = input.strip_prefix(&config.prefix) {
-        input.trim()
-    } else {
-        return RVec::new();
-    };
+    set_status(None);
+
+    // anyrun has already removed the prefix
+    let input = input.trim();
 
     let responses: Vec<ApiResponse> = match reqwest::blocking::get(format!(
         "https://api.dictionaryapi.dev/api/v2/entries/en/{}",
         input
     )) {
+        // The API responds with 404 if there are no definitions for the word
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => return RVec::new(),
         Ok(response) => match response.json() {
             Ok(response) => response,
             Err(why) => {
                 eprintln!("Error deserializing response: {}", why);
+                set_status(Some("Invalid response from the dictionary API".to_string()));
                 return RVec::new();
             }
         },
         Err(why) => {
             eprintln!("Error fetching dictionary result: {}", why);
+            set_status(Some("Failed to reach the dictionary API".to_string()));
             return RVec::new();
         }
     };
@@ -90,6 +82,15 @@ pub fn get_matches(input: RString, config: &Config) -> RVec<Match> {
                             use_pango: false,
                             icon: ROption::RSome("accessories-dictionary".into()),
                             id: ROption::RNone,
+                            highlight: ROption::RNone,
+                            image: ROption::RNone,
+                            keybind: ROption::RNone,
+                            term: ROption::RNone,
+                            default_selected: ROption::RNone,
+                            completion: ROption::RNone,
+                            score: ROption::RNone,
+                            hint: ROption::RNone,
+                            badge: ROption::RNone,
                         })
                         .collect::<RVec<_>>()
                 })
@@ -100,9 +101,18 @@ pub fn get_matches(input: RString, config: &Config) -> RVec<Match> {
 }
 
 #[info]
-fn info() -> PluginInfo {
+fn info(config: Option<&Config>) -> PluginInfo {
     PluginInfo {
         name: "Dictionary".into(),
         icon: "accessories-dictionary".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: config
+            .map(|config| config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
@@ -0,0 +1,172 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+use abi_stable::std_types::{ROption, RString, RVec};
+use anyrun_plugin::*;
+use serde::Deserialize;
+
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
+pub struct Config {
+    prefix: String,
+    #[config(default = 10, min = 1)]
+    max_entries: usize,
+    /// Show hidden files even if the input doesn't start with a `.`
+    show_hidden: bool,
+    /// Use the thumbnails from the XDG thumbnail cache as the icons
+    #[config(default = true)]
+    thumbnails: bool,
+}
+
+mod thumbnail;
+
+struct File {
+    name: String,
+    path: PathBuf,
+    directory: bool,
+}
+
+#[init]
+pub fn init(config_dir: RString) -> Config {
+    load_config(&config_dir, "files.ron")
+}
+
+#[info]
+pub fn info(config: Option<&Config>) -> PluginInfo {
+    PluginInfo {
+        name: "Files".into(),
+        icon: "system-file-manager".into(),
+        requires: vec!["xdg-open".into()].into(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: config
+            .map(|config| config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
+    }
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => PathBuf::from(format!("{}{}", env::var("HOME").unwrap_or_default(), rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// The longest prefix all of the names start with
+fn common_prefix<'a>(mut names: impl Iterator<Item = &'a str>) -> &'a str {
+    let first = match names.next() {
+        Some(first) => first,
+        None => return "",
+    };
+
+    names.fold(first, |prefix, name| {
+        let len = prefix
+            .char_indices()
+            .zip(name.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(name.len()), |((i, _), _)| i);
+        &prefix[..len]
+    })
+}
+
+#[get_matches]
+pub fn get_matches(input: RString, config: &Config) -> RVec<Match> {
+    // anyrun has already removed the prefix
+    if !input.starts_with('/') && !input.starts_with('~') {
+        return RVec::new();
+    }
+    let input = input.as_str();
+
+    // Split the input into the directory and the start of the file name
+    let (dir, start) = match input.rsplit_once('/') {
+        Some((dir, start)) => (format!("{}/", dir), start),
+        None => (format!("{}/", input), ""),
+    };
+
+    let read_dir = match fs::read_dir(expand_home(&dir)) {
+        Ok(read_dir) => read_dir,
+        Err(_) => return RVec::new(),
+    };
+
+    let mut files = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(start)
+                || (name.starts_with('.') && !start.starts_with('.') && !config.show_hidden)
+            {
+                return None;
+            }
+
+            Some(File {
+                name,
+                directory: entry.path().is_dir(),
+                path: entry.path(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Like in shells, Tab first completes the part all of the files have in common
+    let common = common_prefix(files.iter().map(|file| file.name.as_str())).to_string();
+
+    files.truncate(config.max_entries);
+    files
+        .into_iter()
+        .map(|file| {
+            let completion = if common.len() > start.len() {
+                format!("{}{}{}", config.prefix, dir, common)
+            } else if file.directory {
+                format!("{}{}{}/", config.prefix, dir, file.name)
+            } else {
+                format!("{}{}{}", config.prefix, dir, file.name)
+            };
+
+            Match {
+                title: if file.directory {
+                    format!("{}/", file.name).into()
+                } else {
+                    file.name.into()
+                },
+                description: ROption::RSome(file.path.display().to_string().into()),
+                use_pango: false,
+                icon: ROption::RSome(if file.directory {
+                    "folder".into()
+                } else {
+                    config
+                        .thumbnails
+                        .then(|| thumbnail::thumbnail(&file.path))
+                        .flatten()
+                        .unwrap_or_else(|| thumbnail::mime_icon(&file.path))
+                        .into()
+                }),
+                id: ROption::RNone,
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RSome(completion.into()),
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            }
+        })
+        .collect()
+}
+
+#[handler]
+pub fn handler(selection: Match) -> HandleResult {
+    if let Err(why) = Command::new("xdg-open")
+        .arg(selection.description.unwrap().as_str())
+        .spawn()
+    {
+        eprintln!("Error running xdg-open: {}", why);
+    }
+
+    HandleResult::Close
+}
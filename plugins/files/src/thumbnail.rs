@@ -0,0 +1,74 @@
+use std::{env, fs, os::unix::ffi::OsStrExt, path::Path};
+
+/// The thumbnail sizes in the XDG thumbnail cache, from the smallest
+const SIZES: &[&str] = &["normal", "large", "x-large", "xx-large"];
+
+/// The path of an up to date thumbnail of the file in the XDG thumbnail cache, if there is one.
+///
+/// Thumbnails are named after the MD5 hash of the file's URI, as described in the
+/// [Thumbnail Managing Standard](https://specifications.freedesktop.org/thumbnail-spec/thumbnail-spec-latest.html).
+pub fn thumbnail(path: &Path) -> Option<String> {
+    let cache = env::var("XDG_CACHE_HOME")
+        .unwrap_or_else(|_| format!("{}/.cache", env::var("HOME").unwrap_or_default()));
+    let name = format!("{:x}.png", md5::compute(file_uri(path)));
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+
+    SIZES.iter().find_map(|size| {
+        let thumbnail = format!("{}/thumbnails/{}/{}", cache, size, name);
+        // Thumbnails older than the file are outdated
+        let thumbnail_modified = fs::metadata(&thumbnail)
+            .and_then(|meta| meta.modified())
+            .ok()?;
+        (thumbnail_modified >= modified).then_some(thumbnail)
+    })
+}
+
+/// The `file://` URI of the path, escaped the same way GLib does it
+fn file_uri(path: &Path) -> String {
+    let mut uri = "file://".to_string();
+    // Non UTF-8 paths are escaped byte by byte as well
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"!$&'()*+,-./:=@_~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// The name of the icon for the type of the file
+pub fn mime_icon(path: &Path) -> String {
+    match mime_guess::from_path(path).first() {
+        Some(mime) => match mime.type_().as_str() {
+            "image" | "audio" | "video" | "text" | "font" => {
+                format!("{}-x-generic", mime.type_())
+            }
+            _ => mime.essence_str().replace('/', "-"),
+        },
+        None => "text-x-generic".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn file_uris() {
+        assert_eq!(
+            file_uri(Path::new("/home/user/My Pictures/a&b.png")),
+            "file:///home/user/My%20Pictures/a&b.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("/tmp/ä#.txt")),
+            "file:///tmp/%C3%A4%23.txt"
+        );
+        assert_eq!(
+            file_uri(Path::new(OsStr::from_bytes(b"/tmp/\xE4.txt"))),
+            "file:///tmp/%E4.txt"
+        );
+    }
+}
@@ -8,11 +8,16 @@ use std::{fs, os::unix::prelude::OsStrExt, process::Command};
 #[derive(Deserialize)]
 struct Config {
     max_entries: usize,
+    #[serde(default)]
+    min_score: i64,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { max_entries: 3 }
+        Self {
+            max_entries: 3,
+            min_score: 0,
+        }
     }
 }
 
@@ -101,6 +106,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                     description: ROption::RSome(path.clone().into()),
                     use_pango: false,
                     id: ROption::RSome(IndexAction::Open as u64),
+                    highlight: ROption::RNone,
+                    image: ROption::RNone,
+                    keybind: ROption::RNone,
+                    term: ROption::RNone,
+                    default_selected: ROption::RNone,
+                    completion: ROption::RNone,
+                    score: ROption::RNone,
+                    hint: ROption::RNone,
+                    badge: ROption::RNone,
                     icon: ROption::RSome("document-open".into()),
                 },
                 Match {
@@ -108,6 +122,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                     description: ROption::RSome(path.into()),
                     use_pango: false,
                     id: ROption::RSome(IndexAction::CopyPath as u64),
+                    highlight: ROption::RNone,
+                    image: ROption::RNone,
+                    keybind: ROption::RNone,
+                    term: ROption::RNone,
+                    default_selected: ROption::RNone,
+                    completion: ROption::RNone,
+                    score: ROption::RNone,
+                    hint: ROption::RNone,
+                    badge: ROption::RNone,
                     icon: ROption::RSome("edit-copy".into()),
                 },
                 Match {
@@ -115,6 +138,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                     description: ROption::RNone,
                     use_pango: false,
                     id: ROption::RSome(IndexAction::Back as u64),
+                    highlight: ROption::RNone,
+                    image: ROption::RNone,
+                    keybind: ROption::RNone,
+                    term: ROption::RNone,
+                    default_selected: ROption::RNone,
+                    completion: ROption::RNone,
+                    score: ROption::RNone,
+                    hint: ROption::RNone,
+                    badge: ROption::RNone,
                     icon: ROption::RSome("edit-undo".into()),
                 },
             ]
@@ -129,6 +161,7 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                 .filter_map(|(id, index_entry)| {
                     matcher
                         .fuzzy_match(&index_entry.path.as_os_str().to_string_lossy(), &input)
+                        .filter(|score| meets_min_score(&input, *score, state.config.min_score))
                         .map(|val| (index_entry, id, val))
                 })
                 .collect::<Vec<_>>();
@@ -138,7 +171,7 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
             index.truncate(state.config.max_entries);
             index
                 .into_iter()
-                .map(|(entry_index, id, _)| Match {
+                .map(|(entry_index, id, score)| Match {
                     title: entry_index
                         .path
                         .file_name()
@@ -156,6 +189,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                         "text-x-generic".into()
                     }),
                     id: ROption::RSome(id as u64),
+                    highlight: ROption::RNone,
+                    image: ROption::RNone,
+                    keybind: ROption::RNone,
+                    term: ROption::RNone,
+                    default_selected: ROption::RNone,
+                    completion: ROption::RNone,
+                    score: ROption::RSome(score),
+                    hint: ROption::RNone,
+                    badge: ROption::RNone,
                 })
                 .collect()
         }
@@ -167,5 +209,10 @@ pub fn info() -> PluginInfo {
     PluginInfo {
         name: "Kidex".into(),
         icon: "folder".into(),
+        requires: vec!["xdg-open".into()].into(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: ROption::RNone,
     }
 }
@@ -0,0 +1,327 @@
+use abi_stable::std_types::{ROption, RString, RVec};
+use anyrun_plugin::*;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Duration, Local, Months, NaiveDate,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, PluginConfig)]
+#[serde(default)]
+pub struct Config {
+    /// Without a prefix the plugin only responds to inputs it recognizes
+    prefix: String,
+    /// The format of the resulting dates, see the `chrono` documentation for the syntax
+    #[config(default = "%Y-%m-%d".to_string())]
+    date_format: String,
+}
+
+#[init]
+fn init(config_dir: RString) -> Config {
+    let mut config: Config = load_config(&config_dir, "quickmath.ron");
+    // Formatting a date with an invalid format panics, so it can't be used
+    if !valid_date_format(&config.date_format) {
+        eprintln!(
+            "Invalid date_format {:?} in quickmath.ron, using the default",
+            config.date_format
+        );
+        config.date_format = Config::default().date_format;
+    }
+    config
+}
+
+#[info]
+fn info(config: Option<&Config>) -> PluginInfo {
+    PluginInfo {
+        name: "Quick Math".into(),
+        icon: "accessories-calculator".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: config
+            .map(|config| config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
+    }
+}
+
+#[get_matches]
+fn get_matches(input: RString, config: &Config) -> RVec<Match> {
+    // anyrun has already removed the prefix
+    let input = input.to_lowercase();
+
+    match percentage(&input).or_else(|| date_math(&input, &config.date_format)) {
+        Some((title, description)) => vec![Match {
+            title: title.into(),
+            description: ROption::RSome(description.into()),
+            use_pango: false,
+            icon: ROption::RNone,
+            id: ROption::RNone,
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RNone,
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        }]
+        .into(),
+        None => RVec::new(),
+    }
+}
+
+#[handler]
+fn handler(selection: Match) -> HandleResult {
+    HandleResult::Copy(selection.title.into_bytes())
+}
+
+/// Percentages like "20% of 250", "250 + 20%" and "50 is what % of 200"
+fn percentage(input: &str) -> Option<(String, String)> {
+    let input = input.replace("percent", "%").replace('%', " % ");
+    let words = input.split_whitespace().collect::<Vec<_>>();
+
+    match words.as_slice() {
+        [percent, "%", "of", value] => {
+            let (percent, value) = (number(percent)?, number(value)?);
+            Some((
+                format_number(value * percent / 100.0),
+                format!("{}% of {}", format_number(percent), format_number(value)),
+            ))
+        }
+        [value, operator @ ("+" | "-"), percent, "%"] => {
+            let (value, percent) = (number(value)?, number(percent)?);
+            let change = value * percent / 100.0;
+            let result = if *operator == "+" {
+                value + change
+            } else {
+                value - change
+            };
+            Some((
+                format_number(result),
+                format!(
+                    "{} {} {}%",
+                    format_number(value),
+                    operator,
+                    format_number(percent)
+                ),
+            ))
+        }
+        [part, "is", "what", "%", "of", whole] => {
+            let (part, whole) = (number(part)?, number(whole)?);
+            if whole == 0.0 {
+                return None;
+            }
+            Some((
+                format!("{}%", format_number(part / whole * 100.0)),
+                format!("{} of {}", format_number(part), format_number(whole)),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Date math like "days until 2025-12-25", "days since 2020-01-01", "3 weeks from today" and
+/// "2 months ago"
+fn date_math(input: &str, date_format: &str) -> Option<(String, String)> {
+    let words = input.split_whitespace().collect::<Vec<_>>();
+    let today = Local::now().date_naive();
+
+    match words.as_slice() {
+        ["days", "until" | "till", date] => {
+            let days = (date_arg(date)? - today).num_days();
+            Some((days_text(days), format!("From today until {}", date)))
+        }
+        ["days", "since", date] => {
+            let days = (today - date_arg(date)?).num_days();
+            Some((days_text(days), format!("From {} until today", date)))
+        }
+        ["days", "between", from, "and", to] => {
+            let days = (date_arg(to)? - date_arg(from)?).num_days();
+            Some((days_text(days), format!("From {} until {}", from, to)))
+        }
+        [amount, unit, "from" | "after", date] => {
+            let date = shift(date_arg(date)?, number(amount)?, unit, false)?;
+            Some((
+                date.format(date_format).to_string(),
+                date.format("%A").to_string(),
+            ))
+        }
+        [amount, unit, "before", date] => {
+            let date = shift(date_arg(date)?, number(amount)?, unit, true)?;
+            Some((
+                date.format(date_format).to_string(),
+                date.format("%A").to_string(),
+            ))
+        }
+        [amount, unit, "ago"] => {
+            let date = shift(today, number(amount)?, unit, true)?;
+            Some((
+                date.format(date_format).to_string(),
+                date.format("%A").to_string(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Whether dates can be formatted with the format, formatting them with an invalid one panics
+fn valid_date_format(date_format: &str) -> bool {
+    StrftimeItems::new(date_format).all(|item| !matches!(item, Item::Error))
+}
+
+/// A date in the input, either a relative day or `YYYY-MM-DD`
+fn date_arg(word: &str) -> Option<NaiveDate> {
+    let today = Local::now().date_naive();
+    match word {
+        "today" | "now" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        _ => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+    }
+}
+
+/// Move the date by a whole amount of days, weeks, months or years
+fn shift(date: NaiveDate, amount: f64, unit: &str, back: bool) -> Option<NaiveDate> {
+    if amount.fract() != 0.0 || amount < 0.0 || amount > u32::MAX as f64 {
+        return None;
+    }
+    let amount = amount as u32;
+
+    let days = |days: i64| {
+        if back {
+            date.checked_sub_signed(Duration::days(days))
+        } else {
+            date.checked_add_signed(Duration::days(days))
+        }
+    };
+    let months = |months: u32| {
+        if back {
+            date.checked_sub_months(Months::new(months))
+        } else {
+            date.checked_add_months(Months::new(months))
+        }
+    };
+
+    match unit {
+        "day" | "days" => days(amount as i64),
+        "week" | "weeks" => days(amount as i64 * 7),
+        "month" | "months" => months(amount),
+        "year" | "years" => months(amount.checked_mul(12)?),
+        _ => None,
+    }
+}
+
+fn days_text(days: i64) -> String {
+    if days.abs() == 1 {
+        format!("{} day", days)
+    } else {
+        format!("{} days", days)
+    }
+}
+
+fn number(word: &str) -> Option<f64> {
+    word.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// Format the number without trailing zeros, rounded to 6 decimals
+fn format_number(number: f64) -> String {
+    let formatted = format!("{:.6}", number);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    if formatted == "-0" {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn result(title: &str, description: &str) -> Option<(String, String)> {
+        Some((title.to_string(), description.to_string()))
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(percentage("20% of 250"), result("50", "20% of 250"));
+        assert_eq!(
+            percentage("12.5 percent of 80"),
+            result("10", "12.5% of 80")
+        );
+        assert_eq!(percentage("250 + 20%"), result("300", "250 + 20%"));
+        assert_eq!(percentage("250 - 10 %"), result("225", "250 - 10%"));
+        assert_eq!(
+            percentage("50 is what % of 200"),
+            result("25%", "50 of 200")
+        );
+        assert_eq!(percentage("5 is what % of 0"), None);
+        assert_eq!(percentage("20% of inf"), None);
+        assert_eq!(percentage("20 of 250"), None);
+    }
+
+    #[test]
+    fn date_maths() {
+        assert_eq!(
+            date_math("days between 2024-01-01 and 2024-03-01", "%Y-%m-%d"),
+            result("60 days", "From 2024-01-01 until 2024-03-01")
+        );
+        assert_eq!(
+            date_math("days between 2024-01-02 and 2024-01-01", "%Y-%m-%d"),
+            result("-1 day", "From 2024-01-02 until 2024-01-01")
+        );
+        assert_eq!(
+            date_math("days since yesterday", "%Y-%m-%d"),
+            result("1 day", "From yesterday until today")
+        );
+        assert_eq!(
+            date_math("3 weeks from 2024-01-01", "%Y-%m-%d"),
+            result("2024-01-22", "Monday")
+        );
+        assert_eq!(
+            date_math("1 month before 2024-03-31", "%d.%m.%Y"),
+            result("29.02.2024", "Thursday")
+        );
+        assert_eq!(date_math("3 parsecs from today", "%Y-%m-%d"), None);
+        assert_eq!(date_math("days until 2024-13-01", "%Y-%m-%d"), None);
+    }
+
+    #[test]
+    fn shifts() {
+        let start = date(2024, 1, 31);
+        assert_eq!(shift(start, 1.0, "day", false), Some(date(2024, 2, 1)));
+        assert_eq!(shift(start, 2.0, "weeks", true), Some(date(2024, 1, 17)));
+        // The day is clamped to the end of shorter months
+        assert_eq!(shift(start, 1.0, "month", false), Some(date(2024, 2, 29)));
+        assert_eq!(shift(start, 1.0, "year", true), Some(date(2023, 1, 31)));
+        assert_eq!(shift(start, 1.5, "days", false), None);
+        assert_eq!(shift(start, -1.0, "days", false), None);
+        assert_eq!(shift(start, 1.0, "fortnights", false), None);
+        assert_eq!(shift(start, u32::MAX as f64, "years", false), None);
+    }
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(format_number(50.0), "50");
+        assert_eq!(format_number(2.5), "2.5");
+        assert_eq!(format_number(1.0 / 3.0), "0.333333");
+        assert_eq!(format_number(-12.25), "-12.25");
+        assert_eq!(format_number(-0.0000001), "0");
+    }
+
+    #[test]
+    fn date_formats() {
+        assert!(valid_date_format("%Y-%m-%d"));
+        assert!(valid_date_format("%A, %e %B %Y"));
+        assert!(!valid_date_format("%Q"));
+        assert!(!valid_date_format("%Y-%"));
+    }
+}
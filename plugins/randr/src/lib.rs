@@ -1,9 +1,11 @@
-use std::{env, fs};
+use std::{env, fs, path::PathBuf};
 
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
 use fuzzy_matcher::FuzzyMatcher;
-use randr::{dummy::Dummy, hyprland::Hyprland, Configure, Monitor, Randr};
+use randr::{
+    dummy::Dummy, hyprland::Hyprland, sway::Sway, wlr_randr::WlrRandr, Configure, Monitor, Randr,
+};
 use serde::Deserialize;
 
 mod randr;
@@ -12,6 +14,11 @@ mod randr;
 struct Config {
     prefix: String,
     max_entries: usize,
+    #[serde(default)]
+    min_score: i64,
+    /// Where the applied layout is written to in the format of the compositor config
+    #[serde(default)]
+    persist_path: Option<String>,
 }
 
 impl Default for Config {
@@ -19,6 +26,8 @@ impl Default for Config {
         Config {
             prefix: ":dp".to_string(),
             max_entries: 5,
+            min_score: 0,
+            persist_path: None,
         }
     }
 }
@@ -26,25 +35,86 @@ impl Default for Config {
 enum InnerState {
     None,
     Position(Monitor),
+    Mode(Monitor),
 }
 
 pub struct State {
     randr: Box<dyn Randr + Send + Sync>,
     config: Config,
     inner: InnerState,
+    /// The layout before the first change, saved so that it can be restored in a later session
+    snapshot: Option<Vec<Monitor>>,
 }
 
-#[init]
-pub fn init(config_dir: RString) -> State {
-    // Determine which Randr implementation should be used
-    let randr: Box<dyn Randr + Send + Sync> = if env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+/// The ID of the match restoring the snapshot
+const RESTORE_ID: u64 = u64::MAX - 1;
+/// The ID of the match saving the current layout to `persist_path`
+const SAVE_ID: u64 = u64::MAX - 2;
+/// Set on the ID of a monitor for the match entering its mode submenu
+const MODE_ID: u64 = 1 << 32;
+
+/// Where the snapshot of the layout is saved, it is kept until the layout is restored
+fn snapshot_path() -> PathBuf {
+    PathBuf::from(env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string()))
+        .join("anyrun-randr-snapshot.ron")
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => PathBuf::from(format!("{}{}", env::var("HOME").unwrap_or_default(), rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Write the layout of the monitors to `persist_path`, so that it can be sourced from the
+/// compositor config to apply it again after a restart
+fn persist_layout(config: &Config, randr: &dyn Randr) {
+    if let Some(path) = &config.persist_path {
+        let layout = randr
+            .get_monitors()
+            .iter()
+            .map(|mon| randr.layout_line(mon) + "\n")
+            .collect::<String>();
+        if let Err(why) = fs::write(expand_home(path), layout) {
+            eprintln!("Error persisting the Randr layout: {}", why);
+        }
+    }
+}
+
+/// Save the layout before the first change so that it can be restored
+fn save_snapshot(state: &State) {
+    if state.snapshot.is_some() {
+        return;
+    }
+
+    match ron::to_string(&state.randr.get_monitors()) {
+        Ok(snapshot) => {
+            if let Err(why) = fs::write(snapshot_path(), snapshot) {
+                eprintln!("Error saving the Randr snapshot: {}", why);
+            }
+        }
+        Err(why) => eprintln!("Error serializing the Randr snapshot: {}", why),
+    }
+}
+
+/// Determine which Randr implementation should be used. The monitors are read when it is created.
+fn backend() -> Box<dyn Randr + Send + Sync> {
+    if env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
         Box::new(Hyprland::new())
+    } else if env::var("SWAYSOCK").is_ok() {
+        Box::new(Sway::new())
+    } else if env::var("WAYLAND_DISPLAY").is_ok() {
+        Box::new(WlrRandr::new())
     } else {
         Box::new(Dummy)
-    };
+    }
+}
 
+#[init]
+pub fn init(config_dir: RString) -> State {
     State {
-        randr,
+        randr: backend(),
         config: match fs::read_to_string(format!("{}/randr.ron", config_dir)) {
             Ok(content) => ron::from_str(&content).unwrap_or_default(),
             Err(why) => {
@@ -53,14 +123,26 @@ pub fn init(config_dir: RString) -> State {
             }
         },
         inner: InnerState::None,
+        snapshot: fs::read_to_string(snapshot_path())
+            .ok()
+            .and_then(|content| ron::from_str(&content).ok()),
     }
 }
 
 #[info]
-pub fn info() -> PluginInfo {
+pub fn info(state: Option<&State>) -> PluginInfo {
     PluginInfo {
         name: "Randr".into(),
         icon: "video-display".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: state
+            .map(|state| state.config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
 
@@ -68,14 +150,34 @@ pub fn info() -> PluginInfo {
 pub fn handler(_match: Match, state: &mut State) -> HandleResult {
     match &state.inner {
         InnerState::None => {
-            state.inner = InnerState::Position(
-                state
-                    .randr
-                    .get_monitors()
-                    .into_iter()
-                    .find(|mon| mon.id == _match.id.unwrap())
-                    .unwrap(),
-            );
+            if _match.id.unwrap() == RESTORE_ID {
+                for mon in state.snapshot.take().unwrap() {
+                    state.randr.restore(&mon);
+                }
+                if let Err(why) = fs::remove_file(snapshot_path()) {
+                    eprintln!("Error removing the Randr snapshot: {}", why);
+                }
+                persist_layout(&state.config, &*backend());
+                return HandleResult::Close;
+            }
+
+            if _match.id.unwrap() == SAVE_ID {
+                persist_layout(&state.config, &*state.randr);
+                return HandleResult::Close;
+            }
+
+            let id = _match.id.unwrap();
+            let mon = state
+                .randr
+                .get_monitors()
+                .into_iter()
+                .find(|mon| mon.id == id & !MODE_ID)
+                .unwrap();
+            state.inner = if id & MODE_ID != 0 {
+                InnerState::Mode(mon)
+            } else {
+                InnerState::Position(mon)
+            };
             HandleResult::Refresh(true)
         }
         InnerState::Position(mon) => {
@@ -94,10 +196,34 @@ pub fn handler(_match: Match, state: &mut State) -> HandleResult {
                 .find(|mon| mon.id == rel_id as u64)
                 .unwrap();
 
+            save_snapshot(state);
+
             state
                 .randr
                 .configure(mon, Configure::from_id(action, &rel_mon));
 
+            // The monitors of the backend in the state are from before the change
+            persist_layout(&state.config, &*backend());
+
+            HandleResult::Close
+        }
+        InnerState::Mode(mon) => {
+            if _match.id.unwrap() == u64::MAX {
+                state.inner = InnerState::None;
+                return HandleResult::Refresh(false);
+            }
+
+            let mode = state
+                .randr
+                .get_modes(mon)
+                .swap_remove(_match.id.unwrap() as usize);
+
+            save_snapshot(state);
+
+            state.randr.set_mode(mon, &mode);
+
+            persist_layout(&state.config, &*backend());
+
             HandleResult::Close
         }
     }
@@ -105,27 +231,91 @@ pub fn handler(_match: Match, state: &mut State) -> HandleResult {
 
 #[get_matches]
 pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
-    let input = if let Some(input) = input.strip_prefix(&state.config.prefix) {
-        input.trim()
-    } else {
-        return RVec::new();
-    };
+    // anyrun has already removed the prefix, which is not needed in the exclusive mode of the
+    // submenu
+    let input = input.trim();
 
     let matcher = fuzzy_matcher::skim::SkimMatcherV2::default().smart_case();
     let mut vec = match &state.inner {
         InnerState::None => state
-            .randr
-            .get_monitors()
-            .into_iter()
-            .map(|mon| Match {
-                title: format!("Change position of {}", mon.name).into(),
+            .snapshot
+            .as_ref()
+            .map(|_| Match {
+                title: "Restore layout".into(),
                 description: ROption::RSome(
-                    format!("{}x{} at {}x{}", mon.width, mon.height, mon.x, mon.y).into(),
+                    "Restore the layout from before the monitors were changed".into(),
                 ),
                 use_pango: false,
-                icon: ROption::RSome("object-flip-horizontal".into()),
-                id: ROption::RSome(mon.id),
+                icon: ROption::RSome("document-revert".into()),
+                id: ROption::RSome(RESTORE_ID),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
             })
+            .into_iter()
+            .chain(state.config.persist_path.as_ref().map(|path| Match {
+                title: "Save current layout".into(),
+                description: ROption::RSome(format!("Write the layout to {}", path).into()),
+                use_pango: false,
+                icon: ROption::RSome("document-save".into()),
+                id: ROption::RSome(SAVE_ID),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            }))
+            .chain(state.randr.get_monitors().into_iter().flat_map(|mon| {
+                [
+                    Match {
+                        title: format!("Change position of {}", mon.name).into(),
+                        description: ROption::RSome(
+                            format!("{}x{} at {}x{}", mon.width, mon.height, mon.x, mon.y).into(),
+                        ),
+                        use_pango: false,
+                        icon: ROption::RSome("object-flip-horizontal".into()),
+                        id: ROption::RSome(mon.id),
+                        highlight: ROption::RNone,
+                        image: ROption::RNone,
+                        keybind: ROption::RNone,
+                        term: ROption::RNone,
+                        default_selected: ROption::RNone,
+                        completion: ROption::RNone,
+                        score: ROption::RNone,
+                        hint: ROption::RNone,
+                        badge: ROption::RNone,
+                    },
+                    Match {
+                        title: format!("Change mode of {}", mon.name).into(),
+                        description: ROption::RSome(
+                            format!("{}x{} @ {:.2} Hz", mon.width, mon.height, mon.refresh_rate)
+                                .into(),
+                        ),
+                        use_pango: false,
+                        icon: ROption::RSome("video-display".into()),
+                        id: ROption::RSome(mon.id | MODE_ID),
+                        highlight: ROption::RNone,
+                        image: ROption::RNone,
+                        keybind: ROption::RNone,
+                        term: ROption::RNone,
+                        default_selected: ROption::RNone,
+                        completion: ROption::RNone,
+                        score: ROption::RNone,
+                        hint: ROption::RNone,
+                        badge: ROption::RNone,
+                    },
+                ]
+            }))
             .collect::<RVec<_>>(),
         InnerState::Position(mon) => {
             let mut vec = state
@@ -145,6 +335,10 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                                 Configure::Above(&_mon),
                             ]
                             .iter()
+                            .filter(|configure| {
+                                state.randr.can_mirror()
+                                    || !matches!(configure, Configure::Mirror(_))
+                            })
                             .map(|configure| Match {
                                 title: format!("{} {}", configure.to_string(), _mon.name).into(),
                                 description: ROption::RNone,
@@ -152,6 +346,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                                 icon: ROption::RSome(configure.icon().into()),
                                 // Store 2 32 bit IDs in the single 64 bit integer, a bit of a hack
                                 id: ROption::RSome(_mon.id << 32 | Into::<u64>::into(configure)),
+                                highlight: ROption::RNone,
+                                image: ROption::RNone,
+                                keybind: ROption::RNone,
+                                term: ROption::RNone,
+                                default_selected: ROption::RNone,
+                                completion: ROption::RNone,
+                                score: ROption::RNone,
+                                hint: ROption::RNone,
+                                badge: ROption::RNone,
                             })
                             .collect::<Vec<_>>(),
                         )
@@ -166,6 +369,15 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                 use_pango: false,
                 icon: ROption::RSome(Configure::Zero.icon().into()),
                 id: ROption::RSome((&Configure::Zero).into()),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
             });
 
             vec.push(Match {
@@ -174,6 +386,66 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
                 use_pango: false,
                 icon: ROption::RSome("edit-undo".into()),
                 id: ROption::RSome(u64::MAX),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            });
+
+            vec
+        }
+        InnerState::Mode(mon) => {
+            let mut vec = state
+                .randr
+                .get_modes(mon)
+                .iter()
+                .enumerate()
+                .map(|(id, mode)| Match {
+                    title: format!(
+                        "{}x{} @ {:.2} Hz",
+                        mode.width, mode.height, mode.refresh_rate
+                    )
+                    .into(),
+                    description: ROption::RNone,
+                    use_pango: false,
+                    icon: ROption::RSome("video-display".into()),
+                    id: ROption::RSome(id as u64),
+                    highlight: ROption::RNone,
+                    image: ROption::RNone,
+                    keybind: ROption::RNone,
+                    term: ROption::RNone,
+                    default_selected: ROption::RSome(mode.current),
+                    completion: ROption::RNone,
+                    score: ROption::RNone,
+                    hint: ROption::RNone,
+                    badge: if mode.current {
+                        ROption::RSome("Current".into())
+                    } else {
+                        ROption::RNone
+                    },
+                })
+                .collect::<RVec<_>>();
+
+            vec.push(Match {
+                title: "Back".into(),
+                description: ROption::RSome("Return to the previous menu".into()),
+                use_pango: false,
+                icon: ROption::RSome("edit-undo".into()),
+                id: ROption::RSome(u64::MAX),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
             });
 
             vec
@@ -183,13 +455,23 @@ pub fn get_matches(input: RString, state: &State) -> RVec<Match> {
     .filter_map(|_match| {
         matcher
             .fuzzy_match(&_match.title, input)
+            .filter(|score| meets_min_score(input, *score, state.config.min_score))
             .map(|score| (_match, score))
     })
     .collect::<Vec<_>>();
 
     vec.sort_by(|a, b| b.1.cmp(&a.1));
 
-    vec.truncate(state.config.max_entries);
+    // The submenus, and the main menu without input, are shown in full so that every option can be
+    // reached
+    if matches!(state.inner, InnerState::None) && !input.is_empty() {
+        vec.truncate(state.config.max_entries);
+    }
 
-    vec.into_iter().map(|(_match, _)| _match).collect()
+    vec.into_iter()
+        .map(|(mut _match, score)| {
+            _match.score = ROption::RSome(score);
+            _match
+        })
+        .collect()
 }
@@ -8,4 +8,16 @@ impl Randr for Dummy {
     }
 
     fn configure(&self, _mon: &super::Monitor, _config: super::Configure) {}
+
+    fn restore(&self, _mon: &super::Monitor) {}
+
+    fn get_modes(&self, _mon: &super::Monitor) -> Vec<super::Mode> {
+        Vec::new()
+    }
+
+    fn set_mode(&self, _mon: &super::Monitor, _mode: &super::Mode) {}
+
+    fn layout_line(&self, _mon: &super::Monitor) -> String {
+        String::new()
+    }
 }
@@ -1,23 +1,60 @@
+use std::process::Command;
+
 use hyprland::{
     data,
     keyword::Keyword,
     shared::{HyprData, HyprDataVec},
 };
+use serde::Deserialize;
+
+use super::{Configure, Mode, Monitor, Randr};
 
-use super::{Configure, Monitor, Randr};
+/// The modes of a monitor from `hyprctl monitors -j`, which the hyprland crate doesn't expose
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorModes {
+    name: String,
+    /// Formatted like `1920x1080@60.00Hz`
+    #[serde(default)]
+    available_modes: Vec<String>,
+}
 
 pub struct Hyprland {
     monitors: Vec<data::Monitor>,
+    modes: Vec<MonitorModes>,
 }
 
 impl Hyprland {
     pub fn new() -> Self {
+        let modes = match Command::new("hyprctl").args(["monitors", "-j"]).output() {
+            Ok(output) => serde_json::from_slice(&output.stdout).unwrap_or_else(|why| {
+                eprintln!("Error parsing the hyprctl output: {}", why);
+                Vec::new()
+            }),
+            Err(why) => {
+                eprintln!("Error running hyprctl: {}", why);
+                Vec::new()
+            }
+        };
+
         Self {
             monitors: data::Monitors::get().unwrap().to_vec(),
+            modes,
         }
     }
 }
 
+/// Parse a mode formatted like `1920x1080@60.00Hz`
+fn parse_mode(mode: &str) -> Option<(u32, u32, f32)> {
+    let (size, refresh_rate) = mode.strip_suffix("Hz")?.split_once('@')?;
+    let (width, height) = size.split_once('x')?;
+    Some((
+        width.parse().ok()?,
+        height.parse().ok()?,
+        refresh_rate.parse().ok()?,
+    ))
+}
+
 impl Randr for Hyprland {
     fn get_monitors(&self) -> Vec<Monitor> {
         self.monitors
@@ -138,4 +175,56 @@ impl Randr for Hyprland {
             .expect("Failed to configure monitor"),
         }
     }
+
+    fn restore(&self, mon: &Monitor) {
+        Keyword::set(
+            "monitor",
+            format!(
+                "{},{}x{}@{},{}x{},{}",
+                mon.name, mon.width, mon.height, mon.refresh_rate, mon.x, mon.y, mon.scale
+            ),
+        )
+        .expect("Failed to restore monitor");
+    }
+
+    fn get_modes(&self, mon: &Monitor) -> Vec<Mode> {
+        self.modes
+            .iter()
+            .find(|modes| modes.name == mon.name)
+            .map(|modes| {
+                modes
+                    .available_modes
+                    .iter()
+                    .filter_map(|mode| parse_mode(mode))
+                    .map(|(width, height, refresh_rate)| Mode {
+                        width,
+                        height,
+                        refresh_rate,
+                        // The listed refresh rates are rounded to 2 decimals
+                        current: width == mon.width
+                            && height == mon.height
+                            && (refresh_rate - mon.refresh_rate).abs() < 0.01,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_mode(&self, mon: &Monitor, mode: &Mode) {
+        Keyword::set(
+            "monitor",
+            format!(
+                "{},{}x{}@{},{}x{},{}",
+                mon.name, mode.width, mode.height, mode.refresh_rate, mon.x, mon.y, mon.scale
+            ),
+        )
+        .expect("Failed to configure monitor");
+    }
+
+    fn layout_line(&self, mon: &Monitor) -> String {
+        format!(
+            "monitor={},{}x{}@{},{}x{},{}",
+            mon.name, mon.width, mon.height, mon.refresh_rate, mon.x, mon.y, mon.scale
+        )
+    }
 }
@@ -1,7 +1,11 @@
+use serde::{Deserialize, Serialize};
+
 pub mod dummy;
 pub mod hyprland;
+pub mod sway;
+pub mod wlr_randr;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub struct Monitor {
     pub x: i32,
     pub y: i32,
@@ -13,6 +17,16 @@ pub struct Monitor {
     pub id: u64,
 }
 
+/// A resolution and refresh rate supported by a monitor
+#[derive(Clone)]
+pub struct Mode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: f32,
+    /// Whether the monitor is currently using the mode
+    pub current: bool,
+}
+
 pub enum Configure<'a> {
     Mirror(&'a Monitor),
     LeftOf(&'a Monitor),
@@ -76,4 +90,52 @@ impl<'a> From<&Configure<'a>> for u64 {
 pub trait Randr {
     fn get_monitors(&self) -> Vec<Monitor>;
     fn configure(&self, mon: &Monitor, config: Configure);
+    /// Whether `Configure::Mirror` is supported, it is not offered otherwise
+    fn can_mirror(&self) -> bool {
+        true
+    }
+    /// Apply the exact position, resolution and scale of a previously saved monitor
+    fn restore(&self, mon: &Monitor);
+    /// The modes the monitor supports
+    fn get_modes(&self, mon: &Monitor) -> Vec<Mode>;
+    /// Change the resolution and refresh rate of the monitor, keeping its position and scale
+    fn set_mode(&self, mon: &Monitor, mode: &Mode);
+    /// The line applying the position, mode and scale of the monitor in the format of the
+    /// compositor config, for persisting the layout
+    fn layout_line(&self, mon: &Monitor) -> String;
+}
+
+/// Position the monitor relative to another one, for backends that can only set the positions of
+/// the monitors. `logical_size` is the size a monitor takes up in the layout, which the positions
+/// are in, and `set_position` moves a monitor to a position.
+pub fn set_relative_position(
+    mon: &Monitor,
+    config: Configure,
+    logical_size: impl Fn(&Monitor) -> (i32, i32),
+    set_position: impl Fn(&Monitor, i32, i32),
+) {
+    let (width, height) = logical_size(mon);
+    match config {
+        Configure::Mirror(_) => eprintln!("Mirroring is not supported by this backend"),
+        Configure::LeftOf(rel) => {
+            let mut x = rel.x - width;
+            // Move the other monitor to the right to keep the positions positive
+            if x < 0 {
+                set_position(rel, rel.x - x, rel.y);
+                x = 0;
+            }
+            set_position(mon, x, rel.y);
+        }
+        Configure::RightOf(rel) => set_position(mon, rel.x + logical_size(rel).0, rel.y),
+        Configure::Below(rel) => set_position(mon, rel.x, rel.y + logical_size(rel).1),
+        Configure::Above(rel) => {
+            let mut y = rel.y - height;
+            if y < 0 {
+                set_position(rel, rel.x, rel.y - y);
+                y = 0;
+            }
+            set_position(mon, rel.x, y);
+        }
+        Configure::Zero => set_position(mon, 0, 0),
+    }
 }
@@ -0,0 +1,174 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use super::{set_relative_position, Configure, Mode, Monitor, Randr};
+
+/// An output as listed by `swaymsg -t get_outputs`. Disabled outputs have no current mode.
+#[derive(Deserialize)]
+struct Output {
+    name: String,
+    active: bool,
+    /// The area the output takes up in the layout, in logical pixels
+    rect: Rect,
+    #[serde(default)]
+    scale: f32,
+    modes: Vec<OutputMode>,
+    current_mode: Option<OutputMode>,
+}
+
+#[derive(Deserialize)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+#[derive(Deserialize, PartialEq)]
+struct OutputMode {
+    width: u32,
+    height: u32,
+    /// In mHz
+    refresh: u32,
+}
+
+/// Uses `swaymsg` for sway
+pub struct Sway {
+    outputs: Vec<Output>,
+}
+
+impl Sway {
+    pub fn new() -> Self {
+        let outputs = match Command::new("swaymsg")
+            .args(["-t", "get_outputs", "--raw"])
+            .output()
+        {
+            Ok(output) if output.status.success() => serde_json::from_slice(&output.stdout)
+                .unwrap_or_else(|why| {
+                    eprintln!("Error parsing the swaymsg output: {}", why);
+                    Vec::new()
+                }),
+            Ok(output) => {
+                eprintln!(
+                    "swaymsg failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                Vec::new()
+            }
+            Err(why) => {
+                eprintln!("Error running swaymsg: {}", why);
+                Vec::new()
+            }
+        };
+
+        Self { outputs }
+    }
+
+    /// The size the monitor takes up in the layout, positions are in these units
+    fn logical_size(&self, mon: &Monitor) -> (i32, i32) {
+        self.outputs
+            .iter()
+            .find(|output| output.name == mon.name)
+            .map_or((mon.width as i32, mon.height as i32), |output| {
+                (output.rect.width, output.rect.height)
+            })
+    }
+
+    fn set_position(&self, mon: &Monitor, x: i32, y: i32) {
+        self.run(&["output", &mon.name, "pos", &x.to_string(), &y.to_string()]);
+    }
+
+    fn run(&self, args: &[&str]) {
+        match Command::new("swaymsg").args(args).status() {
+            Ok(status) if !status.success() => eprintln!("swaymsg failed with {}", status),
+            Ok(_) => (),
+            Err(why) => eprintln!("Error running swaymsg: {}", why),
+        }
+    }
+}
+
+impl Randr for Sway {
+    fn get_monitors(&self) -> Vec<Monitor> {
+        self.outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.active)
+            .filter_map(|(id, output)| {
+                let mode = output.current_mode.as_ref()?;
+                Some(Monitor {
+                    x: output.rect.x,
+                    y: output.rect.y,
+                    width: mode.width,
+                    height: mode.height,
+                    refresh_rate: mode.refresh as f32 / 1000.0,
+                    scale: output.scale,
+                    name: output.name.clone(),
+                    id: id as u64,
+                })
+            })
+            .collect()
+    }
+
+    fn configure(&self, mon: &Monitor, config: Configure) {
+        set_relative_position(
+            mon,
+            config,
+            |mon| self.logical_size(mon),
+            |mon, x, y| self.set_position(mon, x, y),
+        );
+    }
+
+    fn can_mirror(&self) -> bool {
+        false
+    }
+
+    fn restore(&self, mon: &Monitor) {
+        self.run(&[
+            "output",
+            &mon.name,
+            "mode",
+            &format!("{}x{}@{}Hz", mon.width, mon.height, mon.refresh_rate),
+            "pos",
+            &mon.x.to_string(),
+            &mon.y.to_string(),
+            "scale",
+            &mon.scale.to_string(),
+        ]);
+    }
+
+    fn get_modes(&self, mon: &Monitor) -> Vec<Mode> {
+        self.outputs
+            .iter()
+            .find(|output| output.name == mon.name)
+            .map(|output| {
+                output
+                    .modes
+                    .iter()
+                    .map(|mode| Mode {
+                        width: mode.width,
+                        height: mode.height,
+                        refresh_rate: mode.refresh as f32 / 1000.0,
+                        current: output.current_mode.as_ref() == Some(mode),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_mode(&self, mon: &Monitor, mode: &Mode) {
+        self.run(&[
+            "output",
+            &mon.name,
+            "mode",
+            &format!("{}x{}@{}Hz", mode.width, mode.height, mode.refresh_rate),
+        ]);
+    }
+
+    fn layout_line(&self, mon: &Monitor) -> String {
+        format!(
+            "output {} mode {}x{}@{}Hz pos {} {} scale {}",
+            mon.name, mon.width, mon.height, mon.refresh_rate, mon.x, mon.y, mon.scale
+        )
+    }
+}
@@ -0,0 +1,179 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use super::{set_relative_position, Configure, Mode, Monitor, Randr};
+
+/// An output as listed by `wlr-randr --json`. The position, transform and scale are only listed
+/// for enabled outputs.
+#[derive(Deserialize)]
+struct Output {
+    name: String,
+    enabled: bool,
+    modes: Vec<OutputMode>,
+    position: Option<Position>,
+    #[serde(default)]
+    transform: String,
+    #[serde(default)]
+    scale: f32,
+}
+
+#[derive(Deserialize)]
+struct OutputMode {
+    width: u32,
+    height: u32,
+    refresh: f32,
+    current: bool,
+}
+
+#[derive(Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+/// Uses `wlr-randr` for wlroots based compositors like sway
+pub struct WlrRandr {
+    outputs: Vec<Output>,
+}
+
+impl WlrRandr {
+    pub fn new() -> Self {
+        let outputs = match Command::new("wlr-randr").arg("--json").output() {
+            Ok(output) if output.status.success() => serde_json::from_slice(&output.stdout)
+                .unwrap_or_else(|why| {
+                    eprintln!("Error parsing the wlr-randr output: {}", why);
+                    Vec::new()
+                }),
+            Ok(output) => {
+                eprintln!(
+                    "wlr-randr failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                Vec::new()
+            }
+            Err(why) => {
+                eprintln!("Error running wlr-randr: {}", why);
+                Vec::new()
+            }
+        };
+
+        Self { outputs }
+    }
+
+    /// The size the monitor takes up in the layout, positions are in these units
+    fn logical_size(&self, mon: &Monitor) -> (i32, i32) {
+        let width = (mon.width as f32 / mon.scale).round() as i32;
+        let height = (mon.height as f32 / mon.scale).round() as i32;
+        let rotated = self
+            .outputs
+            .iter()
+            .find(|output| output.name == mon.name)
+            .map_or(false, |output| {
+                output.transform.ends_with("90") || output.transform.ends_with("270")
+            });
+        if rotated {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn set_position(&self, mon: &Monitor, x: i32, y: i32) {
+        self.run(&["--output", &mon.name, "--pos", &format!("{},{}", x, y)]);
+    }
+
+    fn run(&self, args: &[&str]) {
+        match Command::new("wlr-randr").args(args).status() {
+            Ok(status) if !status.success() => eprintln!("wlr-randr failed with {}", status),
+            Ok(_) => (),
+            Err(why) => eprintln!("Error running wlr-randr: {}", why),
+        }
+    }
+}
+
+impl Randr for WlrRandr {
+    fn get_monitors(&self) -> Vec<Monitor> {
+        self.outputs
+            .iter()
+            .enumerate()
+            // Disabled outputs have no current mode and no place in the layout
+            .filter(|(_, output)| output.enabled)
+            .filter_map(|(id, output)| {
+                let mode = output.modes.iter().find(|mode| mode.current)?;
+                let position = output.position.as_ref()?;
+                Some(Monitor {
+                    x: position.x,
+                    y: position.y,
+                    width: mode.width,
+                    height: mode.height,
+                    refresh_rate: mode.refresh,
+                    scale: output.scale,
+                    name: output.name.clone(),
+                    id: id as u64,
+                })
+            })
+            .collect()
+    }
+
+    fn configure(&self, mon: &Monitor, config: Configure) {
+        set_relative_position(
+            mon,
+            config,
+            |mon| self.logical_size(mon),
+            |mon, x, y| self.set_position(mon, x, y),
+        );
+    }
+
+    fn can_mirror(&self) -> bool {
+        false
+    }
+
+    fn restore(&self, mon: &Monitor) {
+        self.run(&[
+            "--output",
+            &mon.name,
+            "--mode",
+            &format!("{}x{}@{}Hz", mon.width, mon.height, mon.refresh_rate),
+            "--pos",
+            &format!("{},{}", mon.x, mon.y),
+            "--scale",
+            &mon.scale.to_string(),
+        ]);
+    }
+
+    fn get_modes(&self, mon: &Monitor) -> Vec<Mode> {
+        self.outputs
+            .iter()
+            .find(|output| output.name == mon.name)
+            .map(|output| {
+                output
+                    .modes
+                    .iter()
+                    .map(|mode| Mode {
+                        width: mode.width,
+                        height: mode.height,
+                        refresh_rate: mode.refresh,
+                        current: mode.current,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_mode(&self, mon: &Monitor, mode: &Mode) {
+        self.run(&[
+            "--output",
+            &mon.name,
+            "--mode",
+            &format!("{}x{}@{}Hz", mode.width, mode.height, mode.refresh_rate),
+        ]);
+    }
+
+    fn layout_line(&self, mon: &Monitor) -> String {
+        format!(
+            "wlr-randr --output {} --mode {}x{}@{}Hz --pos {},{} --scale {}",
+            mon.name, mon.width, mon.height, mon.refresh_rate, mon.x, mon.y, mon.scale
+        )
+    }
+}
@@ -1,6 +1,10 @@
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
 use rink_core::{ast, date, gnu_units, CURRENCY_FILE};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the up-to-date currency conversions could not be loaded
+static OFFLINE: AtomicBool = AtomicBool::new(false);
 
 #[init]
 fn init(_config_dir: RString) -> rink_core::Context {
@@ -16,9 +20,15 @@ fn init(_config_dir: RString) -> rink_core::Context {
             Ok(mut live_defs) => {
                 currency_defs.append(&mut live_defs.defs);
             }
-            Err(why) => println!("Error parsing currency json: {}", why),
+            Err(why) => {
+                println!("Error parsing currency json: {}", why);
+                OFFLINE.store(true, Ordering::Relaxed);
+            }
         },
-        Err(why) => println!("Error fetching up-to-date currency conversions: {}", why),
+        Err(why) => {
+            println!("Error fetching up-to-date currency conversions: {}", why);
+            OFFLINE.store(true, Ordering::Relaxed);
+        }
     }
 
     currency_defs.append(&mut gnu_units::parse_str(CURRENCY_FILE).defs);
@@ -37,6 +47,11 @@ fn info() -> PluginInfo {
     PluginInfo {
         name: "Rink".into(),
         icon: "accessories-calculator".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: ROption::RNone,
     }
 }
 
@@ -44,6 +59,10 @@ fn info() -> PluginInfo {
 fn get_matches(input: RString, ctx: &mut rink_core::Context) -> RVec<Match> {
     match rink_core::one_line(ctx, &input) {
         Ok(result) => {
+            // Only shown alongside a result, as otherwise the plugin would always be visible
+            if OFFLINE.load(Ordering::Relaxed) {
+                set_status(Some("Offline, using bundled exchange rates".to_string()));
+            }
             let (title, desc) = parse_result(result);
             vec![Match {
                 title: title.into(),
@@ -51,10 +70,22 @@ fn get_matches(input: RString, ctx: &mut rink_core::Context) -> RVec<Match> {
                 use_pango: false,
                 icon: ROption::RNone,
                 id: ROption::RNone,
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
             }]
             .into()
         }
-        Err(_) => RVec::new(),
+        Err(_) => {
+            set_status(None);
+            RVec::new()
+        }
     }
 }
 
@@ -0,0 +1,144 @@
+use std::{fs, process::Command};
+
+use abi_stable::std_types::{ROption, RString, RVec};
+use anyrun_plugin::*;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    prefix: String,
+    #[serde(default = "Config::default_max_entries")]
+    max_entries: usize,
+    #[serde(default)]
+    min_score: i64,
+    /// The terminal used for scripts that run in a terminal, if `None` the `terminal` of anyrun,
+    /// `$TERMINAL` or a static list of terminals is used
+    #[serde(default)]
+    terminal: Option<String>,
+    #[serde(default)]
+    scripts: Vec<Script>,
+}
+
+impl Config {
+    fn default_max_entries() -> usize {
+        5
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            max_entries: Self::default_max_entries(),
+            min_score: 0,
+            terminal: None,
+            scripts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Script {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+    /// The command to run, it is run with `sh -c`
+    command: String,
+    /// Run the command in a terminal
+    #[serde(default)]
+    terminal: bool,
+}
+
+#[init]
+pub fn init(config_dir: RString) -> Config {
+    match fs::read_to_string(format!("{}/scripts.ron", config_dir)) {
+        Ok(content) => ron::from_str(&content).unwrap_or_else(|why| {
+            eprintln!("Error parsing scripts plugin config: {}", why);
+            Config::default()
+        }),
+        Err(why) => {
+            eprintln!("Error reading scripts plugin config: {}", why);
+            Config::default()
+        }
+    }
+}
+
+#[info]
+pub fn info(config: Option<&Config>) -> PluginInfo {
+    PluginInfo {
+        name: "Scripts".into(),
+        icon: "text-x-script".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: config
+            .map(|config| config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
+    }
+}
+
+#[get_matches]
+pub fn get_matches(input: RString, config: &Config) -> RVec<Match> {
+    // anyrun has already removed the prefix
+    let input = input.trim();
+
+    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default().smart_case();
+    let mut scripts = config
+        .scripts
+        .iter()
+        .enumerate()
+        .filter_map(|(id, script)| {
+            matcher
+                .fuzzy_match(&script.name, input)
+                .filter(|score| meets_min_score(input, *score, config.min_score))
+                .map(|score| (script, id, score))
+        })
+        .collect::<Vec<_>>();
+
+    scripts.sort_by(|a, b| b.2.cmp(&a.2));
+
+    scripts.truncate(config.max_entries);
+    scripts
+        .into_iter()
+        .map(|(script, id, score)| Match {
+            title: script.name.clone().into(),
+            description: script.description.clone().map(RString::from).into(),
+            use_pango: false,
+            icon: script.icon.clone().map(RString::from).into(),
+            id: ROption::RSome(id as u64),
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RSome(score),
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        })
+        .collect()
+}
+
+#[handler]
+pub fn handler(selection: Match, config: &Config) -> HandleResult {
+    let script = &config.scripts[selection.id.unwrap() as usize];
+
+    if script.terminal {
+        if let Err(why) =
+            spawn_in_terminal(config.terminal.as_deref(), &["sh", "-c", &script.command])
+        {
+            eprintln!("Error running script: {}", why);
+        }
+    } else if let Err(why) = Command::new("sh").arg("-c").arg(&script.command).spawn() {
+        eprintln!("Error running script: {}", why);
+    }
+
+    HandleResult::Close
+}
@@ -1,4 +1,14 @@
-use std::{env, fs, process::Command};
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
@@ -8,6 +18,8 @@ use serde::Deserialize;
 struct Config {
     prefix: String,
     shell: Option<String>,
+    #[serde(default)]
+    show_output: bool,
 }
 
 impl Default for Config {
@@ -15,66 +27,235 @@ impl Default for Config {
         Config {
             prefix: ":sh".to_string(),
             shell: None,
+            show_output: false,
         }
     }
 }
 
+/// The output of a command that is shown in anyrun
+struct Output {
+    lines: Arc<Mutex<Vec<String>>>,
+    /// How many of stdout and stderr have been read until the end
+    closed: Arc<AtomicUsize>,
+}
+
+impl Output {
+    fn finished(&self) -> bool {
+        self.closed.load(Ordering::Relaxed) == 2
+    }
+
+    /// Read the lines of the stream into the output in the background
+    fn read(&self, stream: impl Read + Send + 'static) {
+        let lines = self.lines.clone();
+        let closed = self.closed.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                lines.lock().unwrap().push(line);
+            }
+            closed.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+}
+
+struct State {
+    config: Config,
+    output: Option<Output>,
+}
+
+/// The IDs of the matches shown after the output
+const CLOSE_ID: u64 = 0;
+const REFRESH_ID: u64 = 1;
+
 #[init]
-fn init(config_dir: RString) -> Config {
-    match fs::read_to_string(format!("{}/shell.ron", config_dir)) {
-        Ok(content) => ron::from_str(&content).unwrap_or_default(),
-        Err(_) => Config::default(),
+fn init(config_dir: RString) -> State {
+    State {
+        config: match fs::read_to_string(format!("{}/shell.ron", config_dir)) {
+            Ok(content) => ron::from_str(&content).unwrap_or_default(),
+            Err(_) => Config::default(),
+        },
+        output: None,
     }
 }
 
 #[info]
-fn info() -> PluginInfo {
+fn info(state: Option<&State>) -> PluginInfo {
     PluginInfo {
         name: "Shell".into(),
         icon: "utilities-terminal".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: state
+            .map(|state| state.config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
 
 #[get_matches]
-fn get_matches(input: RString, config: &Config) -> RVec<Match> {
-    if input.starts_with(&config.prefix) {
-        let (_, command) = input.split_once(&config.prefix).unwrap();
-        if !command.is_empty() {
-            vec![Match {
-                title: command.trim().into(),
-                description: ROption::RSome(
-                    config
-                        .shell
-                        .clone()
-                        .unwrap_or_else(|| {
-                            env::var("SHELL").unwrap_or_else(|_| {
-                                "The shell could not be determined!".to_string()
-                            })
-                        })
-                        .into(),
-                ),
+fn get_matches(input: RString, state: &State) -> RVec<Match> {
+    if let Some(output) = &state.output {
+        // Give short commands a moment to finish, so that their whole output is shown at once
+        let start = Instant::now();
+        while !output.finished() && start.elapsed() < Duration::from_secs(1) {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut matches = output
+            .lines
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|line| Match {
+                title: line.clone().into(),
+                description: ROption::RNone,
                 use_pango: false,
                 icon: ROption::RNone,
                 id: ROption::RNone,
-            }]
-            .into()
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            })
+            .collect::<RVec<_>>();
+
+        matches.push(if output.finished() {
+            Match {
+                title: "Close".into(),
+                description: ROption::RSome("The command has finished".into()),
+                use_pango: false,
+                icon: ROption::RSome("window-close".into()),
+                id: ROption::RSome(CLOSE_ID),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            }
         } else {
-            RVec::new()
-        }
+            Match {
+                title: "Refresh".into(),
+                description: ROption::RSome("The command is still running".into()),
+                use_pango: false,
+                icon: ROption::RSome("view-refresh".into()),
+                id: ROption::RSome(REFRESH_ID),
+                highlight: ROption::RNone,
+                image: ROption::RNone,
+                keybind: ROption::RNone,
+                term: ROption::RNone,
+                default_selected: ROption::RNone,
+                completion: ROption::RNone,
+                score: ROption::RNone,
+                hint: ROption::RNone,
+                badge: ROption::RNone,
+            }
+        });
+
+        return matches;
+    }
+
+    // anyrun has already removed the prefix
+    let command = input.as_str();
+    if !command.is_empty() {
+        vec![Match {
+            title: command.trim().into(),
+            description: ROption::RSome(
+                state
+                    .config
+                    .shell
+                    .clone()
+                    .unwrap_or_else(|| {
+                        env::var("SHELL")
+                            .unwrap_or_else(|_| "The shell could not be determined!".to_string())
+                    })
+                    .into(),
+            ),
+            use_pango: false,
+            icon: ROption::RNone,
+            id: ROption::RNone,
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RSome(false),
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RNone,
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        }]
+        .into()
     } else {
         RVec::new()
     }
 }
 
 #[handler]
-fn handler(selection: Match) -> HandleResult {
-    if let Err(why) = Command::new(selection.description.unwrap().as_str())
-        .arg("-c")
-        .arg(selection.title.as_str())
+fn handler(selection: Match, state: &mut State) -> HandleResult {
+    if state.output.is_some() {
+        return match selection.id {
+            ROption::RSome(CLOSE_ID) => HandleResult::Close,
+            ROption::RSome(REFRESH_ID) => HandleResult::Refresh(true),
+            // Selecting a line of the output copies it
+            _ => HandleResult::Copy(selection.title.into_bytes()),
+        };
+    }
+
+    let shell = selection.description.unwrap();
+
+    // Ctrl+Enter runs the command in a terminal
+    if let ROption::RSome(true) = selection.term {
+        if let Err(why) = spawn_in_terminal(None, &[shell.as_str(), "-c", selection.title.as_str()])
+        {
+            println!("Failed to run command: {}", why);
+        }
+
+        return HandleResult::Close;
+    }
+
+    let mut command = Command::new(shell.as_str());
+    command.arg("-c").arg(selection.title.as_str());
+
+    if !state.config.show_output {
+        if let Err(why) = command.spawn() {
+            println!("Failed to run command: {}", why);
+        }
+
+        return HandleResult::Close;
+    }
+
+    match command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
     {
-        println!("Failed to run command: {}", why);
-    }
+        Ok(mut child) => {
+            let output = Output {
+                lines: Arc::new(Mutex::new(Vec::new())),
+                closed: Arc::new(AtomicUsize::new(0)),
+            };
+            output.read(child.stdout.take().unwrap());
+            output.read(child.stderr.take().unwrap());
+            // Reap the process once it exits
+            thread::spawn(move || child.wait());
 
-    HandleResult::Close
+            state.output = Some(output);
+            HandleResult::Refresh(true)
+        }
+        Err(why) => {
+            println!("Failed to run command: {}", why);
+            HandleResult::Close
+        }
+    }
 }
@@ -1,4 +1,9 @@
-use std::{fs, io::stdin};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{stdin, IsTerminal},
+    process::Command,
+};
 
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
@@ -9,6 +14,13 @@ use serde::Deserialize;
 struct Config {
     allow_invalid: bool,
     max_entries: usize,
+    #[serde(default)]
+    dedupe: bool,
+    #[serde(default)]
+    min_score: i64,
+    /// A shell command whose output lines are used when nothing is piped to anyrun
+    #[serde(default)]
+    source_command: Option<String>,
 }
 
 impl Default for Config {
@@ -16,6 +28,9 @@ impl Default for Config {
         Self {
             allow_invalid: false,
             max_entries: 5,
+            dedupe: false,
+            min_score: 0,
+            source_command: None,
         }
     }
 }
@@ -33,9 +48,55 @@ fn init(config_dir: RString) -> State {
         Config::default()
     };
 
-    State {
-        config,
-        lines: stdin().lines().filter_map(|line| line.ok()).collect(),
+    // Reading from a terminal would wait for input that never comes when a command is configured
+    let mut lines = if config.source_command.is_some() && stdin().is_terminal() {
+        Vec::new()
+    } else {
+        stdin()
+            .lines()
+            .filter_map(|line| line.ok())
+            .collect::<Vec<_>>()
+    };
+
+    if lines.is_empty() {
+        if let Some(command) = &config.source_command {
+            lines = run_source_command(command);
+        }
+    }
+
+    // Only keep the first occurrence of each line
+    if config.dedupe {
+        let mut seen = HashSet::new();
+        lines.retain(|line| seen.insert(line.clone()));
+    }
+
+    State { config, lines }
+}
+
+/// The output lines of the command, or none if it fails
+fn run_source_command(command: &str) -> Vec<String> {
+    match Command::new("sh").arg("-c").arg(command).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        Ok(output) => {
+            eprintln!(
+                "Source command failed with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            set_status(Some(format!(
+                "The source command failed with {}",
+                output.status
+            )));
+            Vec::new()
+        }
+        Err(why) => {
+            eprintln!("Error running the source command: {}", why);
+            set_status(Some("Failed to run the source command".to_string()));
+            Vec::new()
+        }
     }
 }
 
@@ -54,8 +115,9 @@ fn get_matches(input: RString, state: &State) -> RVec<Match> {
         .into_iter()
         .filter_map(|line| {
             matcher
-                .fuzzy_match(&line, &input)
-                .map(|score| (line, score))
+                .fuzzy_indices(&line, &input)
+                .filter(|(score, _)| meets_min_score(&input, *score, state.config.min_score))
+                .map(|(score, indices)| (line, score, indices))
         })
         .collect::<Vec<_>>();
 
@@ -63,17 +125,26 @@ fn get_matches(input: RString, state: &State) -> RVec<Match> {
         lines.sort_by(|a, b| b.1.cmp(&a.1));
         lines.truncate(state.config.max_entries);
     } else if state.config.allow_invalid {
-        lines.push((input.into(), 0));
+        lines.push((input.into(), 0, Vec::new()));
     }
 
     lines
         .into_iter()
-        .map(|(line, _)| Match {
+        .map(|(line, score, indices)| Match {
             title: line.into(),
             description: ROption::RNone,
             use_pango: false,
             icon: ROption::RNone,
             id: ROption::RNone,
+            highlight: ROption::RSome(indices.into_iter().map(|index| index as u64).collect()),
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RSome(score),
+            hint: ROption::RNone,
+            badge: ROption::RNone,
         })
         .collect::<Vec<_>>()
         .into()
@@ -84,5 +155,46 @@ fn plugin_info() -> PluginInfo {
     PluginInfo {
         name: "Stdin".into(),
         icon: "format-indent-more".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: ROption::RNone,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyrun_plugin_testing::*;
+
+    fn state(config: &str) -> State {
+        State {
+            config: ron::from_str(config).unwrap(),
+            lines: vec!["alpha".to_string(), "beta".to_string(), "gamma".to_string()],
+        }
+    }
+
+    #[test]
+    fn min_score_drops_weak_matches() {
+        let config = "Config(allow_invalid: false, max_entries: 5)";
+        assert_eq!(titles(&get_matches("ga".into(), &state(config))), ["gamma"]);
+        assert_eq!(
+            titles(&get_matches("aa".into(), &state(config))),
+            ["alpha", "gamma"]
+        );
+
+        let config = "Config(allow_invalid: false, max_entries: 5, min_score: 40)";
+        assert_eq!(titles(&get_matches("aa".into(), &state(config))), ["alpha"]);
+    }
+
+    #[test]
+    fn min_score_without_input() {
+        // Used as dmenu, all lines are shown before anything is typed
+        let config = "Config(allow_invalid: false, max_entries: 5, min_score: 1000)";
+        assert_eq!(
+            titles(&get_matches("".into(), &state(config))),
+            ["alpha", "beta", "gamma"]
+        );
     }
 }
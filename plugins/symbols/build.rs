@@ -6,21 +6,57 @@ use std::{
 
 fn main() {
     let string = fs::read_to_string("res/UnicodeData.txt").expect("Failed to load unicode data!");
+    let blocks = fs::read_to_string("res/Blocks.txt").expect("Failed to load unicode blocks!");
     let mut file = File::create(format!("{}/unicode.rs", env::var("OUT_DIR").unwrap()))
         .expect("Unable to create unicode output file!");
 
-    file.write_all(b"#[allow(text_direction_codepoint_in_literal)]\nconst UNICODE_CHARS: &[(&str, &str)] = &[\n")
+    // The ranges of the blocks as (start, end, name), in ascending order
+    let blocks = blocks
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (range, name) = line.split_once("; ")?;
+            let (start, end) = range.split_once("..")?;
+            Some((
+                u32::from_str_radix(start, 16).unwrap(),
+                u32::from_str_radix(end, 16).unwrap(),
+                name,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    file.write_all(b"#[allow(text_direction_codepoint_in_literal)]\nconst UNICODE_CHARS: &[(&str, &str, &str)] = &[\n")
         .unwrap();
     string.lines().for_each(|line| {
         let fields = line.split(';').collect::<Vec<_>>();
-        let chr = match char::from_u32(u32::from_str_radix(fields[0], 16).unwrap()) {
+        let code = u32::from_str_radix(fields[0], 16).unwrap();
+        let chr = match char::from_u32(code) {
             Some(char) => char,
             None => return,
         };
+        // Characters of blocks newer than the block data have no block
+        let block = match blocks.binary_search_by(|(start, end, _)| {
+            if *end < code {
+                std::cmp::Ordering::Less
+            } else if *start > code {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        }) {
+            Ok(index) => blocks[index].2,
+            Err(_) => "",
+        };
 
         if fields[1] != "<control>" {
-            file.write_all(format!("(r#\"{}\"#, r#\"{}\"#),\n", fields[1], chr).as_bytes())
-                .unwrap();
+            file.write_all(
+                format!(
+                    "(r#\"{}\"#, r#\"{}\"#, r#\"{}\"#),\n",
+                    fields[1], chr, block
+                )
+                .as_bytes(),
+            )
+            .unwrap();
         }
     });
 
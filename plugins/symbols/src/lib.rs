@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
 
 use abi_stable::std_types::{ROption, RString, RVec};
 use anyrun_plugin::*;
@@ -11,23 +11,23 @@ include!(concat!(env!("OUT_DIR"), "/unicode.rs"));
 struct Symbol {
     chr: String,
     name: String,
+    /// The Unicode block of the character, custom symbols don't have one
+    block: Option<&'static str>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, PluginConfig)]
+#[serde(default)]
 struct Config {
     prefix: String,
     symbols: HashMap<String, String>,
+    #[config(default = 3, min = 1)]
     max_entries: usize,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            prefix: "".to_string(),
-            symbols: HashMap::new(),
-            max_entries: 3,
-        }
-    }
+    min_score: i64,
+    /// Only include the characters of these Unicode blocks, all of them if empty
+    blocks: Vec<String>,
+    /// Separates a filter for the block names from the symbol name in the input
+    #[config(default = ":".to_string())]
+    block_delimiter: String,
 }
 
 struct State {
@@ -37,45 +37,74 @@ struct State {
 
 #[init]
 fn init(config_dir: RString) -> State {
-    // Try to load the config file, if it does not exist only use the static unicode characters
-    let config = if let Ok(content) = fs::read_to_string(format!("{}/symbols.ron", config_dir)) {
-        ron::from_str(&content).unwrap_or_default()
-    } else {
-        Config::default()
-    };
+    // If the config file does not exist only the static unicode characters are used
+    let config: Config = load_config(&config_dir, "symbols.ron");
 
     let symbols = UNICODE_CHARS
         .iter()
-        .map(|(name, chr)| (name.to_string(), chr.to_string()))
-        .chain(config.symbols.clone().into_iter())
-        .map(|(name, chr)| Symbol { chr, name })
+        .filter(|(_, _, block)| {
+            config.blocks.is_empty()
+                || config
+                    .blocks
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(block))
+        })
+        .map(|(name, chr, block)| Symbol {
+            chr: chr.to_string(),
+            name: name.to_string(),
+            block: Some(*block).filter(|block| !block.is_empty()),
+        })
+        .chain(config.symbols.iter().map(|(name, chr)| Symbol {
+            chr: chr.clone(),
+            name: name.clone(),
+            block: None,
+        }))
         .collect();
 
     State { config, symbols }
 }
 
 #[info]
-fn info() -> PluginInfo {
+fn info(state: Option<&State>) -> PluginInfo {
     PluginInfo {
         name: "Symbols".into(),
         icon: "accessories-character-map".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: state
+            .map(|state| state.config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
 
 #[get_matches]
 fn get_matches(input: RString, state: &State) -> RVec<Match> {
-    let input = if let Some(input) = input.strip_prefix(&state.config.prefix) {
-        input.trim()
-    } else {
-        return RVec::new();
+    // anyrun has already removed the prefix
+    let input = input.trim();
+    // The text before the delimiter filters the symbols by the names of their blocks
+    let (block, input) = match input.split_once(&state.config.block_delimiter) {
+        Some((block, input)) if !state.config.block_delimiter.is_empty() => {
+            (Some(block.trim().to_lowercase()), input.trim())
+        }
+        _ => (None, input),
     };
     let matcher = fuzzy_matcher::skim::SkimMatcherV2::default().ignore_case();
     let mut symbols = state
         .symbols
         .iter()
+        .filter(|symbol| match (&block, symbol.block) {
+            (Some(filter), Some(block)) => block.to_lowercase().contains(filter),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
         .filter_map(|symbol| {
             matcher
                 .fuzzy_match(&symbol.name, input)
+                .filter(|score| meets_min_score(input, *score, state.config.min_score))
                 .map(|score| (symbol, score))
         })
         .collect::<Vec<_>>();
@@ -87,12 +116,27 @@ fn get_matches(input: RString, state: &State) -> RVec<Match> {
 
     symbols
         .into_iter()
-        .map(|(symbol, _)| Match {
+        .map(|(symbol, score)| Match {
             title: symbol.chr.clone().into(),
-            description: ROption::RSome(symbol.name.clone().into()),
+            description: ROption::RSome(
+                match symbol.block {
+                    Some(block) => format!("{} ({})", symbol.name, block),
+                    None => symbol.name.clone(),
+                }
+                .into(),
+            ),
             use_pango: false,
             icon: ROption::RNone,
             id: ROption::RNone,
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RSome(score),
+            hint: ROption::RNone,
+            badge: ROption::RNone,
         })
         .collect()
 }
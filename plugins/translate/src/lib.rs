@@ -151,21 +151,28 @@ fn init(config_dir: RString) -> State {
 }
 
 #[info]
-fn info() -> PluginInfo {
+fn info(state: Option<&State>) -> PluginInfo {
     PluginInfo {
         name: "Translate".into(),
         icon: "preferences-desktop-locale".into(),
+        requires: RVec::new(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: state
+            .map(|state| state.config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
 
 #[get_matches]
 fn get_matches(input: RString, state: &State) -> RVec<Match> {
-    if !input.starts_with(&state.config.prefix) {
-        return RVec::new();
-    }
+    set_status(None);
 
-    // Ignore the prefix
-    let input = &input[state.config.prefix.len()..];
+    // anyrun has already removed the prefix
+    let input = input.as_str();
     let (lang_split, text) = match input.split_once(' ') {
         Some(split) => split,
         None => return RVec::new(),
@@ -236,23 +243,32 @@ fn get_matches(input: RString, state: &State) -> RVec<Match> {
         let futures = matches
             .into_iter()
             .map(|(src, dest)| async move {
-                match src {
+                let response = match src {
                     Some(src) => 
-                (dest.1, state.client.get(format!("https://translate.googleapis.com/translate_a/single?client=gtx&sl={}&tl={}&dt=t&q={}", src.0, dest.0, text)).send().await),
-                    None => (dest.1, state.client.get(format!("https://translate.googleapis.com/translate_a/single?client=gtx&sl=auto&tl={}&dt=t&q={}", dest.0, text)).send().await)
-                }
+                state.client.get(format!("https://translate.googleapis.com/translate_a/single?client=gtx&sl={}&tl={}&dt=t&q={}", src.0, dest.0, text)).send().await,
+                    None => state.client.get(format!("https://translate.googleapis.com/translate_a/single?client=gtx&sl=auto&tl={}&dt=t&q={}", dest.0, text)).send().await
+                };
+                // Read the body in the same task, a response that isn't valid JSON is skipped
+                let json = match response {
+                    Ok(response) => Ok(response.json::<serde_json::Value>().await.ok()),
+                    Err(why) => Err(why),
+                };
+                (dest.1, json)
             });
        
         let res = futures::future::join_all(futures) // Wait for all futures to complete
             .await;
 
+        if res.iter().any(|(_, res)| res.is_err()) {
+            set_status(Some("Failed to reach the translation service".to_string()));
+        }
+
         res
             .into_iter()
             .filter_map(|(name, res)| res
                 .ok()
-                .map(|response| futures::executor::block_on(response.json())
-                    .ok()
-                    .map(|json: serde_json::Value|
+                .flatten()
+                .map(|json|
                         Match {
                             title: json[0]
                                 .as_array()
@@ -276,11 +292,19 @@ fn get_matches(input: RString, state: &State) -> RVec<Match> {
                                 .into()),
                             use_pango: false,
                             icon: ROption::RNone,
-                            id: ROption::RNone
+                            id: ROption::RNone,
+                            highlight: ROption::RNone,
+                            image: ROption::RNone,
+                            keybind: ROption::RNone,
+                            term: ROption::RNone,
+                            default_selected: ROption::RNone,
+                            completion: ROption::RNone,
+                            score: ROption::RNone,
+                            hint: ROption::RNone,
+                            badge: ROption::RNone,
                         }
-                    )
                 )
-            ).flatten().collect::<RVec<_>>()
+            ).collect::<RVec<_>>()
     })
 }
 
@@ -61,31 +61,47 @@ fn init(config_dir: RString) -> Config {
 }
 
 #[info]
-fn info() -> PluginInfo {
+fn info(config: Option<&Config>) -> PluginInfo {
     PluginInfo {
         name: "Websearch".into(),
         icon: "help-about".into(),
+        requires: vec!["xdg-open".into()].into(),
+        keybinds: RVec::new(),
+        raw_input: false,
+        sort_modes: RVec::new(),
+        prefix: config
+            .map(|config| config.prefix.clone())
+            .filter(|prefix| !prefix.is_empty())
+            .map(RString::from)
+            .into(),
     }
 }
 
 #[get_matches]
 fn get_matches(input: RString, config: &Config) -> RVec<Match> {
-    if !input.starts_with(&config.prefix) {
-        RVec::new()
-    } else {
-        config
-            .engines
-            .iter()
-            .enumerate()
-            .map(|(i, engine)| Match {
-                title: input.trim_start_matches(&config.prefix).into(),
-                description: ROption::RSome(format!("Search with {}", engine).into()),
-                use_pango: false,
-                icon: ROption::RNone,
-                id: ROption::RSome(i as u64),
-            })
-            .collect()
-    }
+    // anyrun has already removed the prefix
+    let query = input.as_str();
+    config
+        .engines
+        .iter()
+        .enumerate()
+        .map(|(i, engine)| Match {
+            title: query.into(),
+            description: ROption::RSome(format!("Search with {}", engine).into()),
+            use_pango: false,
+            icon: ROption::RNone,
+            id: ROption::RSome(i as u64),
+            highlight: ROption::RNone,
+            image: ROption::RNone,
+            keybind: ROption::RNone,
+            term: ROption::RNone,
+            default_selected: ROption::RNone,
+            completion: ROption::RNone,
+            score: ROption::RNone,
+            hint: ROption::RNone,
+            badge: ROption::RNone,
+        })
+        .collect()
 }
 
 #[handler]